@@ -0,0 +1,6 @@
+-- roles de administrador, cada rol habilita un subconjunto de rutas admin
+ALTER TABLE admins ADD COLUMN role VARCHAR(30) NOT NULL DEFAULT 'read_only'
+    CHECK (role IN ('owner', 'catalog_editor', 'sales', 'read_only'));
+
+-- los administradores existentes tenian acceso total, se conservan como owner
+UPDATE admins SET role = 'owner';
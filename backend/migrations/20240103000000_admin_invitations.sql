@@ -0,0 +1,18 @@
+-- los administradores desactivados no pueden iniciar sesion
+ALTER TABLE admins ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT true;
+
+-- invitaciones de un solo uso, solo se guarda el hash sha-256 del token
+-- el rol se valida en la aplicacion al crear la invitacion
+CREATE TABLE admin_invitations (
+    id SERIAL PRIMARY KEY,
+    email VARCHAR(255) NOT NULL,
+    name VARCHAR(255),
+    role VARCHAR(30) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    invited_by INTEGER REFERENCES admins(id) ON DELETE SET NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_admin_invitations_email ON admin_invitations(email);
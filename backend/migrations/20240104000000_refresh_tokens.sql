@@ -0,0 +1,18 @@
+-- contador de generacion de tokens, al incrementarlo se invalidan
+-- todos los jwt emitidos antes para ese administrador
+ALTER TABLE admins ADD COLUMN token_version INTEGER NOT NULL DEFAULT 0;
+
+-- refresh tokens rotativos, solo se guarda el hash sha-256
+-- family_id agrupa las rotaciones de una misma sesion para detectar reutilizacion
+CREATE TABLE admin_refresh_tokens (
+    id SERIAL PRIMARY KEY,
+    admin_id INTEGER NOT NULL REFERENCES admins(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    family_id UUID NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_refresh_tokens_admin ON admin_refresh_tokens(admin_id);
+CREATE INDEX idx_refresh_tokens_family ON admin_refresh_tokens(family_id);
@@ -0,0 +1,38 @@
+-- segundo factor totp (rfc 6238), el secreto queda pendiente hasta confirmarse
+-- totp_last_step guarda el ultimo intervalo aceptado para impedir reutilizar un codigo
+ALTER TABLE admins ADD COLUMN totp_secret VARCHAR(64);
+ALTER TABLE admins ADD COLUMN totp_enabled BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE admins ADD COLUMN totp_last_step BIGINT;
+
+-- codigos de recuperacion de un solo uso, solo se guarda el hash
+CREATE TABLE admin_recovery_codes (
+    id SERIAL PRIMARY KEY,
+    admin_id INTEGER NOT NULL REFERENCES admins(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_recovery_codes_admin ON admin_recovery_codes(admin_id);
+
+-- desafio emitido tras validar la contrasena, se canjea por el jwt real
+CREATE TABLE admin_mfa_challenges (
+    id SERIAL PRIMARY KEY,
+    admin_id INTEGER NOT NULL REFERENCES admins(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_mfa_challenges_admin ON admin_mfa_challenges(admin_id);
+
+-- configuracion de seguridad global, siempre una sola fila
+CREATE TABLE security_settings (
+    id INTEGER PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    require_2fa BOOLEAN NOT NULL DEFAULT false,
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+INSERT INTO security_settings (id) VALUES (1);
@@ -0,0 +1,13 @@
+-- registro de intentos de login para bloqueo temporal y consulta de administradores
+-- la ip se guarda como texto para no depender de tipos inet en sqlx
+CREATE TABLE login_attempts (
+    id BIGSERIAL PRIMARY KEY,
+    email VARCHAR(255) NOT NULL,
+    ip VARCHAR(45) NOT NULL,
+    success BOOLEAN NOT NULL,
+    reason VARCHAR(50) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_login_attempts_email ON login_attempts(email, created_at DESC);
+CREATE INDEX idx_login_attempts_ip ON login_attempts(ip, created_at DESC);
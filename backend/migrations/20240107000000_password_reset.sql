@@ -0,0 +1,12 @@
+-- tokens de restablecimiento de contrasena, de un solo uso y con vencimiento
+-- solo se guarda el hash sha-256 del token enviado por email
+CREATE TABLE password_reset_tokens (
+    id SERIAL PRIMARY KEY,
+    admin_id INTEGER NOT NULL REFERENCES admins(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_password_reset_admin ON password_reset_tokens(admin_id);
@@ -0,0 +1,18 @@
+-- registro de acciones de administradores con el estado antes y despues
+-- actor_email se copia para conservar el autor aunque se elimine la cuenta
+CREATE TABLE audit_events (
+    id BIGSERIAL PRIMARY KEY,
+    actor_id INTEGER REFERENCES admins(id) ON DELETE SET NULL,
+    actor_email VARCHAR(255) NOT NULL,
+    ip VARCHAR(45) NOT NULL,
+    action VARCHAR(50) NOT NULL,
+    entity_type VARCHAR(30) NOT NULL,
+    entity_id VARCHAR(255),
+    before JSONB,
+    after JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_events_created ON audit_events(created_at DESC);
+CREATE INDEX idx_audit_events_entity ON audit_events(entity_type, entity_id, created_at DESC);
+CREATE INDEX idx_audit_events_actor ON audit_events(actor_id, created_at DESC);
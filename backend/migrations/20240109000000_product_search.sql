@@ -0,0 +1,14 @@
+-- busqueda de texto completo con pesos: nombre (A), marca, modelo y registro
+-- sanitario (B) por encima de la descripcion (C). la columna generada evita
+-- repetir la expresion en cada consulta y la mantiene al dia sin triggers
+ALTER TABLE products ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('spanish', COALESCE(name, '')), 'A') ||
+    setweight(to_tsvector('spanish',
+        COALESCE(brand, '') || ' ' || COALESCE(model_number, '') || ' ' || COALESCE(registro_sanitario, '')
+    ), 'B') ||
+    setweight(to_tsvector('spanish', COALESCE(description, '')), 'C')
+) STORED;
+
+-- el indice anterior solo cubria nombre y descripcion sin pesos
+DROP INDEX IF EXISTS idx_products_search;
+CREATE INDEX idx_products_search_vector ON products USING gin(search_vector);
@@ -0,0 +1,37 @@
+-- busqueda sin acentos y tolerante a errores de tipeo
+CREATE EXTENSION IF NOT EXISTS unaccent;
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- unaccent() es STABLE y no se puede usar en columnas generadas ni indices,
+-- con el diccionario fijo el resultado no cambia
+CREATE OR REPLACE FUNCTION f_unaccent(text) RETURNS text
+LANGUAGE sql IMMUTABLE PARALLEL SAFE STRICT
+AS $$ SELECT public.unaccent('public.unaccent'::regdictionary, $1) $$;
+
+-- espanol con acentos plegados: "ecografo" encuentra "Ecógrafo"
+CREATE TEXT SEARCH CONFIGURATION spanish_unaccent (COPY = spanish);
+ALTER TEXT SEARCH CONFIGURATION spanish_unaccent
+    ALTER MAPPING FOR hword, hword_part, word WITH unaccent, spanish_stem;
+
+-- el vector anterior usaba la configuracion spanish, se regenera con la nueva
+ALTER TABLE products DROP COLUMN search_vector;
+ALTER TABLE products ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('spanish_unaccent', COALESCE(name, '')), 'A') ||
+    setweight(to_tsvector('spanish_unaccent',
+        COALESCE(brand, '') || ' ' || COALESCE(model_number, '') || ' ' || COALESCE(registro_sanitario, '')
+    ), 'B') ||
+    setweight(to_tsvector('spanish_unaccent', COALESCE(description, '')), 'C')
+) STORED;
+CREATE INDEX idx_products_search_vector ON products USING gin(search_vector);
+
+-- texto normalizado para similitud por trigramas (nombre, marca y modelo)
+ALTER TABLE products ADD COLUMN search_terms TEXT GENERATED ALWAYS AS (
+    lower(f_unaccent(name || ' ' || brand || ' ' || COALESCE(model_number, '')))
+) STORED;
+CREATE INDEX idx_products_search_terms ON products USING gin(search_terms gin_trgm_ops);
+
+-- modelo sin mayusculas, espacios ni guiones: "EBA-200" y "eba 200" coinciden
+ALTER TABLE products ADD COLUMN model_key TEXT GENERATED ALWAYS AS (
+    regexp_replace(lower(f_unaccent(COALESCE(model_number, ''))), '[^a-z0-9]', '', 'g')
+) STORED;
+CREATE INDEX idx_products_model_key ON products(model_key) WHERE model_key <> '';
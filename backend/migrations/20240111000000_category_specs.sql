@@ -0,0 +1,30 @@
+-- especificaciones estructuradas por categoria. los valores siguen en
+-- products.specifications, la definicion fija clave, etiqueta, unidad y tipo
+CREATE TABLE category_spec_definitions (
+    id SERIAL PRIMARY KEY,
+    category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    key VARCHAR(50) NOT NULL,
+    label VARCHAR(100) NOT NULL,
+    unit VARCHAR(20),
+    data_type VARCHAR(20) NOT NULL,
+    -- valores permitidos, solo para data_type = 'enum'
+    options TEXT[] NOT NULL DEFAULT '{}',
+    filterable BOOLEAN NOT NULL DEFAULT true,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (category_id, key),
+    CHECK (data_type IN ('number', 'enum', 'text', 'boolean')),
+    CHECK (key ~ '^[a-z][a-z0-9_]*$')
+);
+
+CREATE INDEX idx_spec_definitions_key ON category_spec_definitions(key);
+
+-- valor numerico de una especificacion, NULL si falta o no es numero. evita
+-- errores de conversion con datos anteriores a las definiciones ("4 h")
+CREATE OR REPLACE FUNCTION spec_number(specs JSONB, spec_key TEXT) RETURNS DOUBLE PRECISION
+LANGUAGE sql IMMUTABLE PARALLEL SAFE
+AS $$
+    SELECT CASE WHEN jsonb_typeof(specs -> spec_key) = 'number'
+        THEN (specs ->> spec_key)::double precision END
+$$;
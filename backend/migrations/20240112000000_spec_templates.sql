@@ -0,0 +1,14 @@
+-- plantilla de especificaciones por categoria: campos obligatorios, limites
+-- numericos y definiciones tambien para regulatory_info
+ALTER TABLE category_spec_definitions
+    ADD COLUMN section VARCHAR(30) NOT NULL DEFAULT 'specifications',
+    ADD COLUMN required BOOLEAN NOT NULL DEFAULT false,
+    ADD COLUMN min_value DOUBLE PRECISION,
+    ADD COLUMN max_value DOUBLE PRECISION,
+    ADD CONSTRAINT category_spec_definitions_section_check
+        CHECK (section IN ('specifications', 'regulatory_info')),
+    ADD CONSTRAINT category_spec_definitions_range_check
+        CHECK (min_value IS NULL OR max_value IS NULL OR min_value <= max_value);
+
+-- con strict_specs se rechazan claves que la plantilla no define
+ALTER TABLE categories ADD COLUMN strict_specs BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,11 @@
+-- orden estable del catalogo con el id como desempate y paginacion por cursor
+
+-- el cursor compara created_at, un nulo dejaria filas fuera de las paginas
+UPDATE products SET created_at = NOW() WHERE created_at IS NULL;
+ALTER TABLE products ALTER COLUMN created_at SET NOT NULL;
+
+-- indices compuestos para que (columna, id) > (valor, id) no recorra la tabla
+CREATE INDEX idx_products_created_id ON products(created_at DESC, id DESC);
+CREATE INDEX idx_products_name_id ON products(name, id);
+CREATE INDEX idx_products_brand_name_id ON products(brand, name, id);
+CREATE INDEX idx_products_warranty_id ON products(warranty_period, id);
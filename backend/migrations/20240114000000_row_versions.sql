@@ -0,0 +1,4 @@
+-- control de concurrencia optimista: cada escritura incrementa la version y
+-- el cliente envia la que leyo en If-Match
+ALTER TABLE products ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE categories ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,24 @@
+-- historial completo de cada producto: una fila por version con la foto del
+-- producto, quien la hizo y cuando. no tiene clave foranea a products para
+-- conservar el historial aunque el producto se elimine
+CREATE TABLE product_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    product_id INTEGER NOT NULL,
+    version INTEGER NOT NULL,
+    action VARCHAR(20) NOT NULL,
+    -- version restaurada, solo para action = 'restore'
+    restored_from INTEGER,
+    snapshot JSONB NOT NULL,
+    author_id INTEGER REFERENCES admins(id) ON DELETE SET NULL,
+    author_email VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (product_id, version),
+    CHECK (action IN ('import', 'create', 'update', 'toggle', 'restore'))
+);
+
+-- estado actual de los productos existentes como primera revision, sin autor
+INSERT INTO product_revisions (product_id, version, action, snapshot, created_at)
+SELECT id, version, 'import',
+    to_jsonb(products) - 'search_vector' - 'search_terms' - 'model_key',
+    COALESCE(updated_at, created_at)
+FROM products;
@@ -0,0 +1,19 @@
+-- papelera: eliminar marca la fila y la purga la borra despues de
+-- TRASH_RETENTION_DAYS. las cotizaciones guardan ids de productos y siguen
+-- resolviendo mientras la fila exista
+ALTER TABLE products ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE categories ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX idx_products_deleted ON products(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_categories_deleted ON categories(deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- una categoria con productos ya no deja a sus productos sin categoria en
+-- silencio, la purga solo borra categorias vacias
+ALTER TABLE products DROP CONSTRAINT products_category_id_fkey;
+ALTER TABLE products ADD CONSTRAINT products_category_id_fkey
+    FOREIGN KEY (category_id) REFERENCES categories(id) ON DELETE RESTRICT;
+
+-- enviar a la papelera y recuperar tambien generan revision
+ALTER TABLE product_revisions DROP CONSTRAINT product_revisions_action_check;
+ALTER TABLE product_revisions ADD CONSTRAINT product_revisions_action_check
+    CHECK (action IN ('import', 'create', 'update', 'toggle', 'restore', 'delete', 'undelete'));
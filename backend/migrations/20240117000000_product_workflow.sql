@@ -0,0 +1,25 @@
+-- flujo de publicacion: el editor envia a revision y el aprobador publica.
+-- publish_at y unpublish_at acotan cuando el sitio publico muestra un
+-- producto publicado
+ALTER TABLE products ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'draft'
+    CHECK (status IN ('draft', 'in_review', 'published', 'archived'));
+ALTER TABLE products ADD COLUMN publish_at TIMESTAMPTZ;
+ALTER TABLE products ADD COLUMN unpublish_at TIMESTAMPTZ;
+ALTER TABLE products ADD CONSTRAINT products_publish_window_check
+    CHECK (publish_at IS NULL OR unpublish_at IS NULL OR publish_at < unpublish_at);
+
+-- los activos siguen visibles, los inactivos quedan archivados
+UPDATE products SET status = CASE WHEN is_active THEN 'published' ELSE 'archived' END;
+
+DROP INDEX idx_products_active;
+ALTER TABLE products DROP COLUMN is_active;
+CREATE INDEX idx_products_status ON products(status);
+
+ALTER TABLE admins DROP CONSTRAINT admins_role_check;
+ALTER TABLE admins ADD CONSTRAINT admins_role_check
+    CHECK (role IN ('owner', 'catalog_editor', 'catalog_approver', 'sales', 'read_only'));
+
+-- 'toggle' queda para las revisiones anteriores al flujo
+ALTER TABLE product_revisions DROP CONSTRAINT product_revisions_action_check;
+ALTER TABLE product_revisions ADD CONSTRAINT product_revisions_action_check
+    CHECK (action IN ('import', 'create', 'update', 'toggle', 'status', 'restore', 'delete', 'undelete'));
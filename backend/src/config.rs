@@ -1,11 +1,16 @@
 use dotenv::dotenv;
-use std::env;
+use std::{env, str::FromStr};
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub database_url: String,
     pub port: u16,
-    pub jwt_secret: String,
+    // hs256 heredado, opcional si hay clave de firma ed25519
+    pub jwt_secret: Option<String>,
+    pub jwt_signing_key_file: Option<String>,
+    pub jwt_verification_key_files: Vec<String>,
+    pub access_token_ttl_minutes: i64,
+    pub refresh_token_ttl_days: i64,
     pub aws_access_key_id: String,
     pub aws_secret_access_key: String,
     pub aws_region: String,
@@ -14,6 +19,139 @@ pub struct Config {
     pub email_from: String,
     pub email_to: String,
     pub cors_origin: Vec<String>,
+    pub admin_panel_url: String,
+    pub invitation_ttl_hours: i64,
+    pub trust_proxy: bool,
+    pub login_throttle: LoginThrottle,
+    pub password_policy: PasswordPolicy,
+    pub password_reset_ttl_minutes: i64,
+    pub password_hashing: PasswordHashing,
+    // reconstruccion periodica del indice de sugerencias, recoge cambios hechos
+    // desde otras instancias del api
+    pub suggest_refresh_secs: u64,
+    // dias en la papelera antes de borrar productos y categorias
+    pub trash_retention_days: i64,
+    // owner que se crea al arrancar si no hay ninguno activo, ver
+    // services::bootstrap
+    pub bootstrap_owner_email: Option<String>,
+    pub bootstrap_owner_password: Option<String>,
+}
+
+// costo de argon2id para hashes nuevos, los hashes guardados con parametros
+// mas debiles se rehashean en el siguiente login exitoso
+#[derive(Clone, Debug)]
+pub struct PasswordHashing {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for PasswordHashing {
+    // los mismos valores que Argon2::default(), minimo recomendado por owasp
+    fn default() -> Self {
+        Self {
+            memory_kib: 19456,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl PasswordHashing {
+    fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        let hashing = Self {
+            memory_kib: parse_env("ARGON2_MEMORY_KIB", defaults.memory_kib)?,
+            iterations: parse_env("ARGON2_ITERATIONS", defaults.iterations)?,
+            parallelism: parse_env("ARGON2_PARALLELISM", defaults.parallelism)?,
+        };
+
+        // falla al arrancar y no en el primer login
+        argon2::Params::new(hashing.memory_kib, hashing.iterations, hashing.parallelism, None)
+            .map_err(|e| format!("ARGON2_* parameters are invalid: {}", e))?;
+
+        Ok(hashing)
+    }
+}
+
+// reglas para contrasenas nuevas, ver services::validation::validate_password_policy
+#[derive(Clone, Debug)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_mixed_case: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 10,
+            require_mixed_case: true,
+            require_digit: true,
+            require_symbol: false,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        Ok(Self {
+            min_length: parse_env("PASSWORD_MIN_LENGTH", defaults.min_length)?,
+            require_mixed_case: parse_env("PASSWORD_REQUIRE_MIXED_CASE", defaults.require_mixed_case)?,
+            require_digit: parse_env("PASSWORD_REQUIRE_DIGIT", defaults.require_digit)?,
+            require_symbol: parse_env("PASSWORD_REQUIRE_SYMBOL", defaults.require_symbol)?,
+        })
+    }
+}
+
+// limites de intentos de login, ver services::login_guard
+#[derive(Clone, Debug)]
+pub struct LoginThrottle {
+    pub max_failures_per_email: i64,
+    pub max_failures_per_ip: i64,
+    pub lockout_base_secs: i64,
+    pub lockout_max_secs: i64,
+    pub failure_window_minutes: i64,
+    pub requests_per_minute: u32,
+}
+
+impl Default for LoginThrottle {
+    fn default() -> Self {
+        Self {
+            max_failures_per_email: 5,
+            max_failures_per_ip: 20,
+            lockout_base_secs: 30,
+            lockout_max_secs: 3600,
+            failure_window_minutes: 60,
+            requests_per_minute: 10,
+        }
+    }
+}
+
+impl LoginThrottle {
+    fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        Ok(Self {
+            max_failures_per_email: parse_env("LOGIN_MAX_FAILURES_PER_EMAIL", defaults.max_failures_per_email)?,
+            max_failures_per_ip: parse_env("LOGIN_MAX_FAILURES_PER_IP", defaults.max_failures_per_ip)?,
+            lockout_base_secs: parse_env("LOGIN_LOCKOUT_BASE_SECS", defaults.lockout_base_secs)?,
+            lockout_max_secs: parse_env("LOGIN_LOCKOUT_MAX_SECS", defaults.lockout_max_secs)?,
+            failure_window_minutes: parse_env("LOGIN_FAILURE_WINDOW_MINUTES", defaults.failure_window_minutes)?,
+            requests_per_minute: parse_env("LOGIN_REQUESTS_PER_MINUTE", defaults.requests_per_minute)?,
+        })
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> Result<T, String> {
+    match env::var(key) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| format!("{} must be a valid value", key)),
+        Err(_) => Ok(default),
+    }
 }
 
 impl Config {
@@ -23,12 +161,17 @@ impl Config {
         Ok(Config {
             database_url: env::var("DATABASE_URL")
                 .map_err(|_| "DATABASE_URL must be set".to_string())?,
-            port: env::var("PORT")
-                .unwrap_or_else(|_| "3000".to_string())
-                .parse()
-                .map_err(|_| "PORT must be a valid number".to_string())?,
-            jwt_secret: env::var("JWT_SECRET")
-                .map_err(|_| "JWT_SECRET must be set".to_string())?,
+            port: parse_env("PORT", 3000)?,
+            jwt_secret: env::var("JWT_SECRET").ok().filter(|s| !s.trim().is_empty()),
+            jwt_signing_key_file: env::var("JWT_SIGNING_KEY_FILE").ok().filter(|s| !s.trim().is_empty()),
+            jwt_verification_key_files: env::var("JWT_VERIFICATION_KEY_FILES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            access_token_ttl_minutes: parse_env("ACCESS_TOKEN_TTL_MINUTES", 15)?,
+            refresh_token_ttl_days: parse_env("REFRESH_TOKEN_TTL_DAYS", 30)?,
             aws_access_key_id: env::var("AWS_ACCESS_KEY_ID")
                 .map_err(|_| "AWS_ACCESS_KEY_ID must be set".to_string())?,
             aws_secret_access_key: env::var("AWS_SECRET_ACCESS_KEY")
@@ -48,6 +191,23 @@ impl Config {
                 .split(',')
                 .map(|s| s.trim().to_string())
                 .collect(),
+            admin_panel_url: env::var("ADMIN_PANEL_URL")
+                .unwrap_or_else(|_| "http://localhost:4321/admin".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            invitation_ttl_hours: parse_env("INVITATION_TTL_HOURS", 72)?,
+            trust_proxy: parse_env("TRUST_PROXY", false)?,
+            login_throttle: LoginThrottle::from_env()?,
+            password_policy: PasswordPolicy::from_env()?,
+            password_reset_ttl_minutes: parse_env("PASSWORD_RESET_TTL_MINUTES", 30)?,
+            password_hashing: PasswordHashing::from_env()?,
+            suggest_refresh_secs: parse_env("SUGGEST_REFRESH_SECS", 300)?,
+            trash_retention_days: match parse_env("TRASH_RETENTION_DAYS", 30)? {
+                days if days >= 1 => days,
+                _ => return Err("TRASH_RETENTION_DAYS must be at least 1".to_string()),
+            },
+            bootstrap_owner_email: env::var("BOOTSTRAP_OWNER_EMAIL").ok().filter(|s| !s.trim().is_empty()),
+            bootstrap_owner_password: env::var("BOOTSTRAP_OWNER_PASSWORD").ok().filter(|s| !s.is_empty()),
         })
     }
 }
@@ -1,11 +1,16 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
+use std::collections::BTreeMap;
 use thiserror::Error;
 
+/// mensajes por campo, la clave es la ruta del campo: `name`,
+/// `specifications.autonomia`
+pub type FieldErrors = BTreeMap<String, String>;
+
 // codigos de error estandarizados, o sea nunca se exponen 
 // detalles internos al cliente
 #[derive(Error, Debug)]
@@ -18,9 +23,33 @@ pub enum ApiError {
     
     #[error("Error de validacion")]
     Validation(String),
+
+    // validacion con un mensaje por campo para que el formulario los marque
+    #[error("Error de validacion por campo")]
+    InvalidFields(FieldErrors),
     
     #[error("Recurso no encontrado")]
     NotFound(String),
+
+    // varios recursos pedidos por clave, se informa cuales no existen
+    #[error("Recursos no encontrados")]
+    MissingResources(Vec<String>),
+
+    // escritura sobre una version vieja, se devuelve la vigente para que el
+    // cliente pueda combinar los cambios
+    #[error("Version desactualizada")]
+    PreconditionFailed { version: i32, current: serde_json::Value },
+
+    #[error("Falta If-Match")]
+    PreconditionRequired,
+
+    // categoria con productos fuera de la papelera, se informa cuantos
+    #[error("Categoria con productos")]
+    CategoryNotEmpty(i64),
+
+    // exportacion que supera el tope de filas, se informa el tope para acotar el rango
+    #[error("Exportacion demasiado grande")]
+    ExportTooLarge(i32),
     
     #[error("Error interno del servidor")]
     Internal(String),
@@ -30,9 +59,16 @@ pub enum ApiError {
     
     #[error("No autorizado")]
     Unauthorized,
+
+    #[error("Acceso prohibido")]
+    Forbidden(String),
+
+    #[error("Se requiere activar 2fa")]
+    MfaEnrollmentRequired,
     
+    // segundos que el cliente debe esperar, se envian en retry-after
     #[error("Lmite de solicitudes excedido")]
-    RateLimitExceeded,
+    RateLimitExceeded(u64),
 
     #[error("RUC invalido")]
     InvalidRuc,
@@ -40,6 +76,26 @@ pub enum ApiError {
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        let retry_after = match self {
+            ApiError::RateLimitExceeded(secs) => Some(secs),
+            _ => None,
+        };
+        let etag = match self {
+            ApiError::PreconditionFailed { version, .. } => Some(version),
+            _ => None,
+        };
+        // detalle adicional del error, nunca datos internos
+        let details = match self {
+            ApiError::InvalidFields(ref fields) => vec![("fields", json!(fields))],
+            ApiError::MissingResources(ref missing) => vec![("missing", json!(missing))],
+            ApiError::CategoryNotEmpty(products) => vec![("products", json!(products))],
+            ApiError::ExportTooLarge(max_rows) => vec![("max_rows", json!(max_rows))],
+            ApiError::PreconditionFailed { version, ref current } => {
+                vec![("current_version", json!(version)), ("current", current.clone())]
+            }
+            _ => Vec::new(),
+        };
+
         let (status, code, message) = match self {
             ApiError::Database(ref e) => {
                 // loguear error real internamente, nunca exponer al cliente
@@ -71,11 +127,41 @@ impl IntoResponse for ApiError {
                 "ERR_VALIDATION",
                 format!("Error de validación: {}", msg),
             ),
+            ApiError::InvalidFields(_) => (
+                StatusCode::BAD_REQUEST,
+                "ERR_VALIDATION",
+                "Error de validación: revise los campos indicados".to_string(),
+            ),
             ApiError::NotFound(_) => (
                 StatusCode::NOT_FOUND,
                 "ERR_NOT_FOUND",
                 "Recurso no encontrado".to_string(),
             ),
+            ApiError::MissingResources(_) => (
+                StatusCode::NOT_FOUND,
+                "ERR_NOT_FOUND",
+                "Algunos recursos no existen".to_string(),
+            ),
+            ApiError::PreconditionFailed { .. } => (
+                StatusCode::PRECONDITION_FAILED,
+                "ERR_PRECONDITION_FAILED",
+                "El recurso fue modificado por otra persona, revise la version vigente".to_string(),
+            ),
+            ApiError::PreconditionRequired => (
+                StatusCode::PRECONDITION_REQUIRED,
+                "ERR_PRECONDITION_REQUIRED",
+                "Falta la cabecera If-Match con la version del recurso".to_string(),
+            ),
+            ApiError::CategoryNotEmpty(_) => (
+                StatusCode::CONFLICT,
+                "ERR_CATEGORY_NOT_EMPTY",
+                "La categoria tiene productos, muevalos o envielos a la papelera primero".to_string(),
+            ),
+            ApiError::ExportTooLarge(_) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "ERR_EXPORT_TOO_LARGE",
+                "La exportacion supera el maximo de filas, acote el rango de fechas".to_string(),
+            ),
             ApiError::Internal(ref msg) => {
                 tracing::error!(
                     error_type = "internal",
@@ -98,7 +184,24 @@ impl IntoResponse for ApiError {
                 "ERR_UNAUTHORIZED",
                 "No autorizado".to_string(),
             ),
-            ApiError::RateLimitExceeded => (
+            ApiError::Forbidden(ref msg) => {
+                tracing::warn!(
+                    error_type = "forbidden",
+                    details = %msg,
+                    "Acceso denegado por rol"
+                );
+                (
+                    StatusCode::FORBIDDEN,
+                    "ERR_FORBIDDEN",
+                    "No tiene permisos para realizar esta accion".to_string(),
+                )
+            }
+            ApiError::MfaEnrollmentRequired => (
+                StatusCode::FORBIDDEN,
+                "ERR_MFA_ENROLLMENT_REQUIRED",
+                "Debe activar la verificacion en dos pasos para continuar".to_string(),
+            ),
+            ApiError::RateLimitExceeded(_) => (
                 StatusCode::TOO_MANY_REQUESTS,
                 "ERR_RATE_LIMIT",
                 "Demasiadas solicitudes, intente en unos minutos".to_string(),
@@ -110,13 +213,109 @@ impl IntoResponse for ApiError {
             ),
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "code": code,
             "message": message,
-        }));
+        });
+        for (name, value) in details {
+            body[name] = value;
+        }
+        let body = Json(body);
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        if let Some(version) = etag {
+            if let Ok(value) = HeaderValue::from_str(&format!("\"{}\"", version)) {
+                response.headers_mut().insert(header::ETAG, value);
+            }
+        }
+        response
     }
 }
 
-pub type ApiResult<T> = Result<T, ApiError>;
\ No newline at end of file
+pub type ApiResult<T> = Result<T, ApiError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // cuerpo json de la respuesta de error
+    async fn body_json(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_rate_limit_incluye_retry_after() {
+        let response = ApiError::RateLimitExceeded(42).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "42");
+    }
+
+    #[tokio::test]
+    async fn test_errores_por_campo_en_el_cuerpo() {
+        let mut fields = FieldErrors::new();
+        fields.insert("specifications.peso".to_string(), "Campo obligatorio".to_string());
+        let response = ApiError::InvalidFields(fields).into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = body_json(response).await;
+        assert_eq!(body["code"], "ERR_VALIDATION");
+        assert_eq!(body["fields"]["specifications.peso"], "Campo obligatorio");
+    }
+
+    #[tokio::test]
+    async fn test_recursos_faltantes_en_el_cuerpo() {
+        let response = ApiError::MissingResources(vec!["bomba-x".to_string()]).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = body_json(response).await;
+        assert_eq!(body["missing"], json!(["bomba-x"]));
+        assert!(body.get("fields").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_version_vigente_en_el_cuerpo() {
+        let error = ApiError::PreconditionFailed { version: 4, current: json!({"id": 1, "version": 4}) };
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(response.headers().get(header::ETAG).unwrap(), "\"4\"");
+
+        let body = body_json(response).await;
+        assert_eq!(body["code"], "ERR_PRECONDITION_FAILED");
+        assert_eq!(body["current_version"], 4);
+        assert_eq!(body["current"]["version"], 4);
+
+        assert_eq!(ApiError::PreconditionRequired.into_response().status(), StatusCode::PRECONDITION_REQUIRED);
+    }
+
+    #[tokio::test]
+    async fn test_categoria_con_productos_en_el_cuerpo() {
+        let response = ApiError::CategoryNotEmpty(3).into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let body = body_json(response).await;
+        assert_eq!(body["code"], "ERR_CATEGORY_NOT_EMPTY");
+        assert_eq!(body["products"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_exportacion_demasiado_grande() {
+        let response = ApiError::ExportTooLarge(50_000).into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = body_json(response).await;
+        assert_eq!(body["code"], "ERR_EXPORT_TOO_LARGE");
+        assert_eq!(body["max_rows"], 50_000);
+    }
+
+    #[test]
+    fn test_otros_errores_sin_retry_after() {
+        let response = ApiError::Unauthorized.into_response();
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
+    }
+}
@@ -13,13 +13,17 @@ mod services;
 mod middleware;
 
 use config::Config;
-use services::email::EmailService;
+use services::{email::EmailService, jwt_keys::JwtKeys, login_guard::{self, LoginRateLimiter}, suggest::SuggestIndex};
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub s3: S3Client,
     pub email: EmailService,
+    pub login_limiter: LoginRateLimiter,
+    pub jwt_keys: Arc<JwtKeys>,
+    pub suggest: Arc<SuggestIndex>,
     pub config: Config,
 }
 
@@ -41,6 +45,10 @@ async fn main() -> anyhow::Result<()> {
     
     tracing::info!("Configuracion cargada exitosamente");
     
+    let jwt_keys = JwtKeys::from_config(&config)
+        .map_err(|e| anyhow::anyhow!("Error al cargar claves jwt: {}", e))?;
+    tracing::info!(claves = jwt_keys.jwks().keys.len(), "Claves jwt cargadas");
+    
     // inicializar pool de base de datos
     let db_pool = db::create_pool(&config.database_url).await?;
     tracing::info!("Pool de conexiones de base de datos creado");
@@ -52,6 +60,10 @@ async fn main() -> anyhow::Result<()> {
         .await?;
     tracing::info!("Migraciones de base de datos completadas");
     
+    services::bootstrap::run(&db_pool, &config)
+        .await
+        .map_err(|e| anyhow::anyhow!("Error al preparar el administrador inicial: {:?}", e))?;
+    
     // inicializar servicios
     let s3_client = services::s3::create_client(&config).await;
     tracing::info!("Cliente S3 inicializado");
@@ -63,13 +75,54 @@ async fn main() -> anyhow::Result<()> {
     let port = config.port;
     let cors_origins = config.cors_origin.clone();
     
+    let login_limiter = login_guard::create_rate_limiter(&config.login_throttle);
+    
+    // el limitador en memoria guarda una entrada por ip, se purgan las inactivas
+    let limiter_cleanup = login_limiter.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
+        loop {
+            interval.tick().await;
+            limiter_cleanup.retain_recent();
+        }
+    });
+    
     let app_state = AppState {
         db: db_pool,
         s3: s3_client,
         email: email_service,
+        login_limiter,
+        jwt_keys: Arc::new(jwt_keys),
+        suggest: Arc::new(SuggestIndex::default()),
         config,
     };
     
+    // indice de sugerencias de busqueda, se reconstruye al cambiar el catalogo
+    // y ademas cada SUGGEST_REFRESH_SECS
+    app_state.suggest.rebuild(&app_state.db).await?;
+    tracing::info!("Indice de sugerencias construido");
+    let suggest_state = app_state.clone();
+    tokio::spawn(async move {
+        let period = std::time::Duration::from_secs(suggest_state.config.suggest_refresh_secs.max(1));
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        loop {
+            interval.tick().await;
+            services::suggest::refresh(&suggest_state);
+        }
+    });
+    
+    // purga de la papelera cada hora, borra lo que supero TRASH_RETENTION_DAYS
+    let trash_state = app_state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            if let Err(e) = services::trash::purge(&trash_state).await {
+                tracing::error!(error = ?e, "Error al purgar la papelera");
+            }
+        }
+    });
+    
     // configurar cors estricto - sin AllowAll
     let cors = CorsLayer::new()
         .allow_origin(AllowOrigin::list(
@@ -89,13 +142,18 @@ async fn main() -> anyhow::Result<()> {
             http::header::CONTENT_TYPE,
             http::header::AUTHORIZATION,
             http::header::ACCEPT,
-        ]);
+            http::header::IF_MATCH,
+        ])
+        // el frontend lee la paginacion de la cabecera Link y la version de
+        // productos y categorias de ETag
+        .expose_headers([http::header::LINK, http::header::ETAG]);
     
     // construir router
     let app = Router::new()
         .route("/health", get(health_check))
+        .route("/.well-known/jwks.json", get(routes::public::jwks))
         .nest("/api", routes::public::routes())
-        .nest("/api/admin", routes::admin::routes())
+        .nest("/api/admin", routes::admin::routes(app_state.clone()))
         .layer(cors)
         .layer(tower_http::trace::TraceLayer::new_for_http())
         .with_state(app_state);
@@ -105,7 +163,8 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Servidor escuchando en {}", addr);
     
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // connect info expone la ip del socket para el bloqueo por ip
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
     
     Ok(())
 }
@@ -1,11 +1,12 @@
 use axum::{
-    extract::{Request, State},
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::request::Parts,
     middleware::Next,
     response::Response,
 };
-use crate::{error::ApiError, services::auth::verify_jwt, AppState};
+use crate::{error::ApiError, services::auth::{verify_jwt, Claims}, AppState};
 
-#[allow(dead_code)]
 pub async fn auth_middleware(
     State(state): State<AppState>,
     mut request: Request,
@@ -21,9 +22,37 @@ pub async fn auth_middleware(
         .strip_prefix("Bearer ")
         .ok_or(ApiError::Unauthorized)?;
     
-    let claims = verify_jwt(token, &state.config.jwt_secret)?;
+    let verified = verify_jwt(&state.db, token, &state.jwt_keys).await?;
+    
+    // con 2fa obligatorio solo se permite activar 2fa o cerrar sesiones
+    if verified.mfa_enrollment_required && !allowed_without_mfa(request.uri().path()) {
+        return Err(ApiError::MfaEnrollmentRequired);
+    }
     
     // se agregaron claims a las extensiones del request para uso en handlers
-    request.extensions_mut().insert(claims);
+    request.extensions_mut().insert(verified.claims);
     Ok(next.run(request).await)
-}
\ No newline at end of file
+}
+
+// el router admin esta anidado, la ruta llega sin el prefijo /api/admin
+fn allowed_without_mfa(path: &str) -> bool {
+    path.starts_with("/2fa/") || path == "/logout-all"
+}
+
+// extractor para que los handlers reciban el admin autenticado, solo funciona
+// en rutas protegidas por auth_middleware, si no hay claims se rechaza
+#[async_trait]
+impl<S> FromRequestParts<S> for Claims
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Claims>()
+            .cloned()
+            .ok_or(ApiError::Unauthorized)
+    }
+}
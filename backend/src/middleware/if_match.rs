@@ -0,0 +1,131 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, HeaderValue},
+};
+use serde::Serialize;
+use std::convert::Infallible;
+
+use crate::error::{ApiError, ApiResult};
+
+/// versiones que el cliente espera modificar, de la cabecera If-Match. las
+/// escrituras de productos y categorias la exigen para no pisar cambios ajenos.
+/// la extraccion nunca rechaza: una cabecera ausente o ilegible se informa en
+/// `check`, que el handler llama despues de verificar permisos, asi un rol sin
+/// permiso recibe 403 y no 428
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfMatch {
+    Missing,
+    Invalid,
+    // `*`: cualquier version vigente
+    Any,
+    Versions(Vec<i32>),
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(header::IF_MATCH) else {
+            return Ok(Self::Missing);
+        };
+        Ok(value.to_str().ok().and_then(IfMatch::parse).unwrap_or(Self::Invalid))
+    }
+}
+
+impl IfMatch {
+    // lista de etiquetas "n" separadas por coma. If-Match usa comparacion
+    // fuerte, una etiqueta debil W/"n" nunca coincide
+    fn parse(value: &str) -> Option<Self> {
+        if value.trim() == "*" {
+            return Some(Self::Any);
+        }
+        let mut versions = Vec::new();
+        for tag in value.split(',').map(str::trim) {
+            if tag.starts_with("W/") {
+                continue;
+            }
+            let version = tag.strip_prefix('"')?.strip_suffix('"')?.parse().ok()?;
+            versions.push(version);
+        }
+        Some(Self::Versions(versions))
+    }
+
+    /// 428 sin cabecera, 400 si no se puede leer y 412 con la version vigente
+    /// y el recurso actual si no coincide
+    pub fn check(&self, version: i32, current: &impl Serialize) -> ApiResult<()> {
+        match self {
+            Self::Missing => Err(ApiError::PreconditionRequired),
+            Self::Invalid => Err(ApiError::BadRequest("Cabecera If-Match no valida".to_string())),
+            Self::Versions(versions) if !versions.contains(&version) => Err(ApiError::PreconditionFailed {
+                version,
+                current: serde_json::to_value(current).map_err(|e| ApiError::Internal(e.to_string()))?,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// cabecera ETag con la version del recurso
+pub fn etag(version: i32) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(&format!("\"{}\"", version)) {
+        headers.insert(header::ETAG, value);
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_lectura_de_if_match() {
+        assert_eq!(IfMatch::parse("\"3\""), Some(IfMatch::Versions(vec![3])));
+        assert_eq!(IfMatch::parse(" \"3\", \"4\" "), Some(IfMatch::Versions(vec![3, 4])));
+        assert_eq!(IfMatch::parse("*"), Some(IfMatch::Any));
+        assert_eq!(IfMatch::parse("W/\"3\""), Some(IfMatch::Versions(vec![])));
+        assert_eq!(IfMatch::parse("3"), None);
+        assert_eq!(IfMatch::parse("\"tres\""), None);
+    }
+
+    #[test]
+    fn test_version_vieja_devuelve_la_vigente() {
+        let current = json!({"id": 1, "version": 5});
+        assert!(IfMatch::Versions(vec![5]).check(5, &current).is_ok());
+        assert!(IfMatch::Any.check(5, &current).is_ok());
+        match IfMatch::Versions(vec![4]).check(5, &current) {
+            Err(ApiError::PreconditionFailed { version, current }) => {
+                assert_eq!(version, 5);
+                assert_eq!(current["id"], 1);
+            }
+            other => panic!("se esperaba 412: {:?}", other),
+        }
+        assert!(IfMatch::Versions(vec![]).check(5, &current).is_err());
+        assert_eq!(etag(5).get(header::ETAG).unwrap(), "\"5\"");
+    }
+
+    #[tokio::test]
+    async fn test_la_extraccion_no_rechaza() {
+        async fn extract(value: Option<&str>) -> IfMatch {
+            let mut builder = axum::http::Request::builder();
+            if let Some(value) = value {
+                builder = builder.header(header::IF_MATCH, value);
+            }
+            let (mut parts, _) = builder.body(()).unwrap().into_parts();
+            let Ok(if_match) = IfMatch::from_request_parts(&mut parts, &()).await;
+            if_match
+        }
+
+        assert_eq!(extract(Some("\"2\"")).await, IfMatch::Versions(vec![2]));
+        assert_eq!(extract(None).await, IfMatch::Missing);
+        assert_eq!(extract(Some("dos")).await, IfMatch::Invalid);
+
+        // el error llega recien en check, despues de los permisos del handler
+        let current = json!({"id": 1, "version": 2});
+        assert!(matches!(IfMatch::Missing.check(2, &current), Err(ApiError::PreconditionRequired)));
+        assert!(matches!(IfMatch::Invalid.check(2, &current), Err(ApiError::BadRequest(_))));
+    }
+}
@@ -1 +1,3 @@
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod client_ip;
+pub mod if_match;
\ No newline at end of file
@@ -2,6 +2,9 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use validator::Validate;
 use chrono::{DateTime, Utc};
+use std::{fmt, str::FromStr};
+use crate::{config::PasswordPolicy, services::validation::validate_password_policy};
+use super::PageMeta;
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Admin {
@@ -10,28 +13,335 @@ pub struct Admin {
     #[serde(skip_serializing)]
     pub password_hash: String,
     pub name: Option<String>,
+    #[sqlx(try_from = "String")]
+    pub role: AdminRole,
+    pub is_active: bool,
+    #[serde(skip_serializing)]
+    pub token_version: i32,
+    #[serde(skip_serializing)]
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool,
+    #[serde(skip_serializing)]
+    pub totp_last_step: Option<i64>,
     pub created_at: DateTime<Utc>,
     pub last_login: Option<DateTime<Utc>>,
 }
 
+// rol guardado en admins.role y replicado en el jwt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminRole {
+    Owner,
+    // revisa y publica lo que envian los editores
+    CatalogApprover,
+    CatalogEditor,
+    Sales,
+    ReadOnly,
+}
+
+// acciones que se autorizan por ruta en routes::admin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    CatalogRead,
+    CatalogWrite,
+    // aprobar, publicar y archivar productos
+    CatalogPublish,
+    QuotesRead,
+    QuotesWrite,
+    ManageAdmins,
+}
+
+impl AdminRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdminRole::Owner => "owner",
+            AdminRole::CatalogApprover => "catalog_approver",
+            AdminRole::CatalogEditor => "catalog_editor",
+            AdminRole::Sales => "sales",
+            AdminRole::ReadOnly => "read_only",
+        }
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        use Permission::*;
+        match self {
+            AdminRole::Owner => true,
+            AdminRole::CatalogApprover => matches!(permission, CatalogRead | CatalogWrite | CatalogPublish),
+            AdminRole::CatalogEditor => matches!(permission, CatalogRead | CatalogWrite),
+            // ventas necesita ver el catalogo para atender cotizaciones
+            AdminRole::Sales => matches!(permission, CatalogRead | QuotesRead | QuotesWrite),
+            AdminRole::ReadOnly => matches!(permission, CatalogRead | QuotesRead),
+        }
+    }
+}
+
+impl fmt::Display for AdminRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AdminRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "owner" => Ok(AdminRole::Owner),
+            "catalog_approver" => Ok(AdminRole::CatalogApprover),
+            "catalog_editor" => Ok(AdminRole::CatalogEditor),
+            "sales" => Ok(AdminRole::Sales),
+            "read_only" => Ok(AdminRole::ReadOnly),
+            other => Err(format!("Rol de administrador desconocido: {}", other)),
+        }
+    }
+}
+
+impl TryFrom<String> for AdminRole {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct LoginRequest {
     #[validate(email)]
     pub email: String,
     
-    #[validate(length(min = 6))]
+    // la politica solo aplica a contrasenas nuevas, aqui basta acotar el largo
+    #[validate(length(min = 1, max = 128))]
     pub password: String,
 }
 
+// tambien se devuelve al rotar el refresh token
 #[derive(Debug, Serialize)]
 pub struct LoginResponse {
     pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
     pub admin: AdminInfo,
 }
 
+// con 2fa activo el login solo devuelve un desafio de corta duracion
+#[derive(Debug, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub challenge_token: String,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginOutcome {
+    Session(LoginResponse),
+    MfaRequired(MfaChallengeResponse),
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyMfaRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub challenge_token: String,
+
+    #[validate(length(max = 16))]
+    pub code: Option<String>,
+
+    #[validate(length(max = 64))]
+    pub recovery_code: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TotpSetupResponse {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TotpCodeRequest {
+    #[validate(length(min = 6, max = 16))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DisableTotpRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub password: String,
+
+    #[validate(length(min = 6, max = 16))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct SecuritySettings {
+    pub require_2fa: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSecuritySettingsRequest {
+    pub require_2fa: bool,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct LoginAttempt {
+    pub id: i64,
+    pub email: String,
+    pub ip: String,
+    pub success: bool,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginAttemptQuery {
+    pub email: Option<String>,
+    pub ip: Option<String>,
+    pub success: Option<bool>,
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginAttemptListResponse {
+    pub attempts: Vec<LoginAttempt>,
+    #[serde(flatten)]
+    pub meta: PageMeta,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RefreshTokenRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub refresh_token: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AdminInfo {
     pub id: i32,
     pub email: String,
     pub name: Option<String>,
+    pub role: AdminRole,
+}
+
+impl From<&Admin> for AdminInfo {
+    fn from(admin: &Admin) -> Self {
+        Self {
+            id: admin.id,
+            email: admin.email.clone(),
+            name: admin.name.clone(),
+            role: admin.role,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct AdminInvitation {
+    pub id: i32,
+    pub email: String,
+    pub name: Option<String>,
+    #[sqlx(try_from = "String")]
+    pub role: AdminRole,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct InviteAdminRequest {
+    #[validate(email)]
+    pub email: String,
+
+    #[validate(length(min = 2, max = 255))]
+    pub name: Option<String>,
+
+    pub role: AdminRole,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(context = PasswordPolicy)]
+pub struct AcceptInvitationRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub token: String,
+
+    #[validate(custom(function = "validate_password_policy", use_context))]
+    pub password: String,
+
+    #[validate(length(min = 2, max = 255))]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateAdminStatusRequest {
+    pub is_active: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(context = PasswordPolicy)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub current_password: String,
+
+    #[validate(custom(function = "validate_password_policy", use_context))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(context = PasswordPolicy)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub token: String,
+
+    #[validate(custom(function = "validate_password_policy", use_context))]
+    pub new_password: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permisos_por_rol() {
+        use Permission::*;
+        let all = [CatalogRead, CatalogWrite, CatalogPublish, QuotesRead, QuotesWrite, ManageAdmins];
+
+        assert!(all.iter().all(|p| AdminRole::Owner.allows(*p)));
+
+        assert!(AdminRole::CatalogApprover.allows(CatalogPublish));
+        assert!(AdminRole::CatalogApprover.allows(CatalogWrite));
+        assert!(!AdminRole::CatalogApprover.allows(QuotesRead));
+
+        assert!(AdminRole::CatalogEditor.allows(CatalogWrite));
+        assert!(!AdminRole::CatalogEditor.allows(CatalogPublish));
+        assert!(!AdminRole::CatalogEditor.allows(QuotesRead));
+        assert!(!AdminRole::CatalogEditor.allows(QuotesWrite));
+
+        assert!(AdminRole::Sales.allows(QuotesWrite));
+        assert!(AdminRole::Sales.allows(CatalogRead));
+        assert!(!AdminRole::Sales.allows(CatalogWrite));
+
+        assert!(AdminRole::ReadOnly.allows(CatalogRead));
+        assert!(AdminRole::ReadOnly.allows(QuotesRead));
+        assert!(!AdminRole::ReadOnly.allows(CatalogWrite));
+        assert!(!AdminRole::ReadOnly.allows(QuotesWrite));
+
+        // solo owner gestiona otras cuentas
+        for role in [AdminRole::CatalogApprover, AdminRole::CatalogEditor, AdminRole::Sales, AdminRole::ReadOnly] {
+            assert!(!role.allows(ManageAdmins));
+        }
+    }
+
+    #[test]
+    fn test_rol_ida_y_vuelta() {
+        for role in [AdminRole::Owner, AdminRole::CatalogApprover, AdminRole::CatalogEditor, AdminRole::Sales, AdminRole::ReadOnly] {
+            assert_eq!(role.as_str().parse::<AdminRole>(), Ok(role));
+        }
+        assert!("superuser".parse::<AdminRole>().is_err());
+    }
 }
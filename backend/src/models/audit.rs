@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use super::PageMeta;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct AuditEventRecord {
+    pub id: i64,
+    pub actor_id: Option<i32>,
+    pub actor_email: String,
+    pub ip: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditEventQuery {
+    pub actor_id: Option<i32>,
+    pub actor_email: Option<String>,
+    pub action: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+    // solo para la exportacion
+    pub format: Option<AuditExportFormat>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditExportFormat {
+    #[default]
+    Csv,
+    Ndjson,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEventListResponse {
+    pub events: Vec<AuditEventRecord>,
+    #[serde(flatten)]
+    pub meta: PageMeta,
+}
@@ -8,7 +8,13 @@ pub struct Category {
     pub name: String,
     pub slug: String,
     pub description: Option<String>,
+    // rechaza claves de especificaciones que la plantilla no define
+    pub strict_specs: bool,
+    // aumenta con cada cambio, se envia en ETag e If-Match
+    pub version: i32,
     pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct CompareQuery {
+    // slugs separados por coma, en el orden de las columnas
+    pub slugs: Option<String>,
+}
+
+/// columna de la comparacion
+#[derive(Debug, Serialize)]
+pub struct ComparedProduct {
+    pub id: i32,
+    pub slug: String,
+    pub name: String,
+    pub category_id: Option<i32>,
+    pub image_url: Option<String>,
+}
+
+/// fila de la comparacion, un valor por producto en el orden de las columnas.
+/// null si el producto no tiene el dato
+#[derive(Debug, Serialize)]
+pub struct ComparisonRow {
+    pub key: String,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    pub values: Vec<serde_json::Value>,
+    pub differs: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ComparisonResponse {
+    pub products: Vec<ComparedProduct>,
+    pub rows: Vec<ComparisonRow>,
+}
@@ -2,8 +2,22 @@ pub mod product;
 pub mod category;
 pub mod quote;
 pub mod admin;
+pub mod audit;
+pub mod pagination;
+pub mod search;
+pub mod spec;
+pub mod compare;
+pub mod revision;
+pub mod trash;
 
 pub use product::*;
 pub use category::*;
 pub use quote::*;
 pub use admin::*;
+pub use audit::*;
+pub use pagination::*;
+pub use search::*;
+pub use spec::*;
+pub use compare::*;
+pub use revision::*;
+pub use trash::*;
\ No newline at end of file
@@ -0,0 +1,81 @@
+use serde::Serialize;
+
+/// metadatos de paginacion comunes a todos los listados, se aplanan en la respuesta
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PageMeta {
+    pub total: i64,
+    pub page: i32,
+    pub limit: i32,
+    pub total_pages: i64,
+    pub has_next: bool,
+    pub has_prev: bool,
+}
+
+impl PageMeta {
+    pub fn new(page: i32, limit: i32, total: i64) -> Self {
+        let limit_i64 = (limit as i64).max(1);
+        let total = total.max(0);
+        let total_pages = (total + limit_i64 - 1) / limit_i64;
+        Self {
+            total,
+            page,
+            limit,
+            total_pages,
+            has_next: (page as i64) < total_pages,
+            has_prev: page > 1,
+        }
+    }
+
+    /// pagina pedida por cursor, sin numero de pagina (`page` en 0)
+    pub fn cursor(limit: i32, total: i64, has_next: bool) -> Self {
+        Self {
+            page: 0,
+            has_next,
+            has_prev: true,
+            ..Self::new(0, limit, total)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meta_de_paginacion() {
+        let meta = PageMeta::new(1, 20, 41);
+        assert_eq!(meta.total_pages, 3);
+        assert!(meta.has_next);
+        assert!(!meta.has_prev);
+
+        let last = PageMeta::new(3, 20, 41);
+        assert!(!last.has_next);
+        assert!(last.has_prev);
+
+        let exact = PageMeta::new(2, 20, 40);
+        assert_eq!(exact.total_pages, 2);
+        assert!(!exact.has_next);
+
+        let empty = PageMeta::new(1, 20, 0);
+        assert_eq!(empty.total_pages, 0);
+        assert!(!empty.has_next);
+        assert!(!empty.has_prev);
+
+        let cursor = PageMeta::cursor(20, 41, false);
+        assert_eq!((cursor.page, cursor.total_pages), (0, 3));
+        assert!(!cursor.has_next);
+        assert!(cursor.has_prev);
+    }
+
+    #[test]
+    fn test_meta_se_aplana_sin_romper_el_formato() {
+        let json = serde_json::to_value(PageMeta::new(2, 10, 25)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "total": 25, "page": 2, "limit": 10,
+                "total_pages": 3, "has_next": true, "has_prev": true
+            })
+        );
+    }
+}
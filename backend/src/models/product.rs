@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
 use validator::Validate;
+use std::{fmt, str::FromStr};
+use super::{PageMeta, SpecRangeFacet};
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Product {
@@ -20,9 +22,73 @@ pub struct Product {
     pub image_url: Option<String>,
     pub additional_images: serde_json::Value,
     pub regulatory_info: serde_json::Value,
-    pub is_active: bool,
+    #[sqlx(try_from = "String")]
+    pub status: ProductStatus,
+    // ventana de publicacion, un producto publicado solo se ve dentro de ella
+    pub publish_at: Option<DateTime<Utc>>,
+    pub unpublish_at: Option<DateTime<Utc>>,
+    // aumenta con cada cambio, se envia en ETag e If-Match
+    pub version: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    // en la papelera desde esta fecha, el sitio publico nunca lo muestra
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+// estado del flujo de publicacion, guardado en products.status. las
+// transiciones permitidas estan en services::workflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductStatus {
+    Draft,
+    InReview,
+    Published,
+    Archived,
+}
+
+impl ProductStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProductStatus::Draft => "draft",
+            ProductStatus::InReview => "in_review",
+            ProductStatus::Published => "published",
+            ProductStatus::Archived => "archived",
+        }
+    }
+}
+
+impl fmt::Display for ProductStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProductStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(ProductStatus::Draft),
+            "in_review" => Ok(ProductStatus::InReview),
+            "published" => Ok(ProductStatus::Published),
+            "archived" => Ok(ProductStatus::Archived),
+            other => Err(format!("Estado de producto no valido: {}", other)),
+        }
+    }
+}
+
+impl TryFrom<String> for ProductStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateProductStatusRequest {
+    pub status: String,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -48,30 +114,101 @@ pub struct CreateProductRequest {
     pub registro_sanitario: String,
     pub specifications: Option<serde_json::Value>,
     pub regulatory_info: Option<serde_json::Value>,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub unpublish_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-pub struct UpdateProductRequest {
-    pub name: Option<String>,
-    pub slug: Option<String>,
+/// campos editables del producto despues de aplicar un merge patch sobre el
+/// producto actual, con las mismas reglas que al crear
+#[derive(Debug, Deserialize, Validate)]
+pub struct ProductFields {
+    #[validate(length(min = 2, max = 255))]
+    pub name: String,
+    #[validate(length(min = 2, max = 255))]
+    pub slug: String,
+    #[validate(length(max = 5000))]
     pub description: Option<String>,
     pub category_id: Option<i32>,
-    pub brand: Option<String>,
+    #[validate(length(min = 1, max = 200))]
+    pub brand: String,
+    #[validate(length(max = 200))]
     pub model_number: Option<String>,
-    pub origin_country: Option<String>,
-    pub warranty_period: Option<i32>,
+    #[validate(length(min = 2, max = 100))]
+    pub origin_country: String,
+    #[validate(range(min = 0, max = 120))]
+    pub warranty_period: i32,
+    #[validate(url)]
     pub technical_sheet_url: Option<String>,
-    pub registro_sanitario: Option<String>,
-    pub specifications: Option<serde_json::Value>,
-    pub regulatory_info: Option<serde_json::Value>,
-    pub is_active: Option<bool>,
+    #[validate(length(min = 1, max = 100))]
+    pub registro_sanitario: String,
+    pub specifications: serde_json::Value,
+    #[validate(url)]
+    pub image_url: Option<String>,
+    pub additional_images: serde_json::Value,
+    pub regulatory_info: serde_json::Value,
+    // las revisiones anteriores al flujo de publicacion no tienen ventana
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub unpublish_at: Option<DateTime<Utc>>,
+}
+
+/// producto dentro de un listado. con busqueda incluye la relevancia y los
+/// fragmentos que coincidieron, marcados con <mark>
+#[derive(Debug, Serialize, FromRow)]
+pub struct ProductListItem {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub product: Product,
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<f32>,
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_highlight: Option<String>,
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_highlight: Option<String>,
+    // valores de orden de la fila, para armar el cursor siguiente
+    #[sqlx(default)]
+    #[serde(skip)]
+    pub cursor_key: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ProductListResponse {
-    pub products: Vec<Product>,
-    pub total: i64,
-    pub page: i32,
-    pub limit: i32,
+    pub products: Vec<ProductListItem>,
+    #[serde(flatten)]
+    pub meta: PageMeta,
+    // posicion opaca para pedir la pagina siguiente con `cursor`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    // busqueda corregida cuando no hubo resultados
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did_you_mean: Option<String>,
+    // solo en el catalogo publico
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<ProductFacets>,
+}
+
+/// cantidad de productos por valor de un filtro
+#[derive(Debug, Serialize, FromRow)]
+pub struct FacetCount {
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub count: i64,
+}
+
+/// conteos de cada dimension calculados con los demas filtros activos, sin el
+/// propio: marcar una marca no oculta las otras marcas
+#[derive(Debug, Serialize)]
+pub struct ProductFacets {
+    pub category: Vec<FacetCount>,
+    pub brand: Vec<FacetCount>,
+    pub origin_country: Vec<FacetCount>,
+    pub warranty: Vec<FacetCount>,
+    // rangos de especificaciones numericas, solo al filtrar por categoria
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub specs: Vec<SpecRangeFacet>,
 }
\ No newline at end of file
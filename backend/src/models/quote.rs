@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use validator::Validate;
 use chrono::{DateTime, Utc};
+use super::PageMeta;
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Quote {
@@ -57,7 +58,6 @@ pub struct UpdateQuoteStatusRequest {
 #[derive(Debug, Serialize)]
 pub struct QuoteListResponse {
     pub quotes: Vec<Quote>,
-    pub total: i64,
-    pub page: i32,
-    pub limit: i32,
+    #[serde(flatten)]
+    pub meta: PageMeta,
 }
\ No newline at end of file
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use super::PageMeta;
+
+/// revision de un producto sin la foto, para el listado
+#[derive(Debug, Serialize, FromRow)]
+pub struct RevisionSummary {
+    pub id: i64,
+    pub product_id: i32,
+    pub version: i32,
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<i32>,
+    pub author_id: Option<i32>,
+    pub author_email: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// revision con la foto completa del producto en esa version
+#[derive(Debug, Serialize, FromRow)]
+pub struct ProductRevision {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub summary: RevisionSummary,
+    pub snapshot: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevisionListResponse {
+    pub revisions: Vec<RevisionSummary>,
+    #[serde(flatten)]
+    pub meta: PageMeta,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevisionQuery {
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: i32,
+    // por defecto la ultima version
+    pub to: Option<i32>,
+}
+
+/// cambio de un campo entre dos revisiones, null si el campo no existia
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevisionDiff {
+    pub product_id: i32,
+    pub from: i32,
+    pub to: i32,
+    pub changes: Vec<FieldChange>,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionKind {
+    // el orden define la prioridad ante coincidencias equivalentes
+    Category,
+    Brand,
+    Product,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Suggestion {
+    #[serde(rename = "type")]
+    pub kind: SuggestionKind,
+    pub slug: String,
+    pub label: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuggestQuery {
+    pub q: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuggestResponse {
+    pub suggestions: Vec<Suggestion>,
+}
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use std::{fmt, str::FromStr};
+use validator::{Validate, ValidationError};
+
+// tipo de valor de una especificacion, guardado en category_spec_definitions.data_type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecDataType {
+    Number,
+    Enum,
+    Text,
+    Boolean,
+}
+
+impl SpecDataType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpecDataType::Number => "number",
+            SpecDataType::Enum => "enum",
+            SpecDataType::Text => "text",
+            SpecDataType::Boolean => "boolean",
+        }
+    }
+}
+
+impl fmt::Display for SpecDataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SpecDataType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "number" => Ok(SpecDataType::Number),
+            "enum" => Ok(SpecDataType::Enum),
+            "text" => Ok(SpecDataType::Text),
+            "boolean" => Ok(SpecDataType::Boolean),
+            other => Err(format!("Tipo de especificacion desconocido: {}", other)),
+        }
+    }
+}
+
+impl TryFrom<String> for SpecDataType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+// columna jsonb del producto donde vive el valor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecSection {
+    #[default]
+    Specifications,
+    RegulatoryInfo,
+}
+
+impl SpecSection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpecSection::Specifications => "specifications",
+            SpecSection::RegulatoryInfo => "regulatory_info",
+        }
+    }
+}
+
+impl fmt::Display for SpecSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SpecSection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "specifications" => Ok(SpecSection::Specifications),
+            "regulatory_info" => Ok(SpecSection::RegulatoryInfo),
+            other => Err(format!("Seccion de especificacion desconocida: {}", other)),
+        }
+    }
+}
+
+impl TryFrom<String> for SpecSection {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SpecDefinition {
+    pub id: i32,
+    pub category_id: i32,
+    pub key: String,
+    pub label: String,
+    pub unit: Option<String>,
+    #[sqlx(try_from = "String")]
+    pub data_type: SpecDataType,
+    #[sqlx(try_from = "String")]
+    pub section: SpecSection,
+    pub required: bool,
+    pub options: Vec<String>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub filterable: bool,
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateSpecDefinitionRequest {
+    // misma clave que en products.specifications, no se puede cambiar despues
+    #[validate(length(min = 1, max = 50), custom(function = "validate_spec_key"))]
+    pub key: String,
+    #[validate(length(min = 1, max = 100))]
+    pub label: String,
+    #[validate(length(min = 1, max = 20))]
+    pub unit: Option<String>,
+    pub data_type: SpecDataType,
+    #[serde(default)]
+    pub section: SpecSection,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub options: Vec<String>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    #[serde(default = "default_filterable")]
+    pub filterable: bool,
+    #[serde(default)]
+    pub position: i32,
+}
+
+// reemplaza la definicion completa salvo clave, tipo y seccion, unit en null la quita
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateSpecDefinitionRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub label: String,
+    #[validate(length(min = 1, max = 20))]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub options: Vec<String>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    #[serde(default = "default_filterable")]
+    pub filterable: bool,
+    #[serde(default)]
+    pub position: i32,
+}
+
+fn default_filterable() -> bool {
+    true
+}
+
+/// minusculas, digitos y guion bajo, empezando por letra: `autonomia_bateria`
+pub fn validate_spec_key(key: &str) -> Result<(), ValidationError> {
+    let mut chars = key.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        let mut error = ValidationError::new("spec_key");
+        error.message = Some("La clave solo admite minusculas, digitos y guion bajo".into());
+        Err(error)
+    }
+}
+
+/// plantilla completa de una categoria, reemplaza todas sus definiciones
+#[derive(Debug, Deserialize)]
+pub struct UpdateSpecTemplateRequest {
+    #[serde(default)]
+    pub strict: bool,
+    pub fields: Vec<CreateSpecDefinitionRequest>,
+}
+
+/// minimo y maximo de una especificacion numerica con los demas filtros activos
+#[derive(Debug, Serialize)]
+pub struct SpecRangeFacet {
+    pub key: String,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub count: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clave_de_especificacion() {
+        assert!(validate_spec_key("autonomia_bateria").is_ok());
+        assert!(validate_spec_key("pantalla2").is_ok());
+        assert!(validate_spec_key("Peso").is_err());
+        assert!(validate_spec_key("2pantalla").is_err());
+        assert!(validate_spec_key("peso-kg").is_err());
+        assert!(validate_spec_key("").is_err());
+    }
+}
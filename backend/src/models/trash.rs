@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use super::{Category, PageMeta, Product};
+
+/// producto en la papelera. purge_at es null mientras una cotizacion lo
+/// referencie, la purga no lo borra
+#[derive(Debug, Serialize, FromRow)]
+pub struct TrashedProduct {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub product: Product,
+    #[serde(skip)]
+    pub quoted: bool,
+    #[sqlx(skip)]
+    pub purge_at: Option<DateTime<Utc>>,
+}
+
+/// categoria en la papelera. purge_at es null mientras tenga productos,
+/// incluidos los que estan en la papelera
+#[derive(Debug, Serialize, FromRow)]
+pub struct TrashedCategory {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub category: Category,
+    #[serde(skip)]
+    pub has_products: bool,
+    #[sqlx(skip)]
+    pub purge_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrashedProductListResponse {
+    pub products: Vec<TrashedProduct>,
+    #[serde(flatten)]
+    pub meta: PageMeta,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrashQuery {
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+}
@@ -0,0 +1,214 @@
+use axum::{
+    extract::{OriginalUri, Query, State},
+    http::{header, HeaderMap},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use crate::{
+    error::{ApiError, ApiResult},
+    models::*,
+    services::{
+        auth::Claims,
+        listing::{page_links, Filter, ListQuery, Pagination},
+    },
+    AppState,
+};
+
+// tope de filas por exportacion, si el filtro lo supera se rechaza en vez de
+// entregar un archivo incompleto
+const EXPORT_MAX_ROWS: i32 = 50_000;
+
+// consulta y exportacion del registro de auditoria, se monta dentro del
+// router autenticado de routes::admin
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/audit-events", get(list_audit_events))
+        .route("/audit-events/export", get(export_audit_events))
+}
+
+// los mismos filtros se aplican al listado, al conteo y a la exportacion
+fn audit_listing(params: &AuditEventQuery) -> ListQuery {
+    ListQuery::new("audit_events")
+        .filter_opt(params.actor_id.map(|id| Filter::eq("actor_id", id)))
+        .filter_opt(params.actor_email.as_deref().map(|email| Filter::eq("lower(actor_email)", email.trim().to_lowercase())))
+        .filter_opt(params.action.as_deref().map(|action| Filter::eq("action", action.trim())))
+        .filter_opt(params.entity_type.as_deref().map(|entity_type| Filter::eq("entity_type", entity_type.trim())))
+        .filter_opt(params.entity_id.as_deref().map(|entity_id| Filter::eq("entity_id", entity_id.trim())))
+        .filter_opt(params.from.map(|from| Filter::gte("created_at", from)))
+        .filter_opt(params.to.map(|to| Filter::lt("created_at", to)))
+}
+
+async fn list_audit_events(
+    State(state): State<AppState>,
+    claims: Claims,
+    OriginalUri(uri): OriginalUri,
+    Query(params): Query<AuditEventQuery>,
+) -> ApiResult<(HeaderMap, Json<AuditEventListResponse>)> {
+    claims.require(Permission::ManageAdmins)?;
+
+    let pagination = Pagination::new(params.page, params.limit, 50, 200);
+    let listing = audit_listing(&params);
+
+    let events = listing
+        .clone()
+        .order_by("created_at DESC, id DESC")
+        .paginate(pagination)
+        .select("*")
+        .build_query_as::<AuditEventRecord>()
+        .fetch_all(&state.db)
+        .await?;
+
+    let (total,): (i64,) = listing.count().build_query_as().fetch_one(&state.db).await?;
+
+    let meta = PageMeta::new(pagination.page, pagination.limit, total);
+
+    Ok((page_links(&uri, &meta), Json(AuditEventListResponse { events, meta })))
+}
+
+async fn export_audit_events(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(params): Query<AuditEventQuery>,
+) -> ApiResult<impl IntoResponse> {
+    claims.require(Permission::ManageAdmins)?;
+
+    // una fila de mas basta para saber si el tope se supera
+    let events = audit_listing(&params)
+        .order_by("created_at ASC, id ASC")
+        .paginate(Pagination { page: 1, limit: EXPORT_MAX_ROWS + 1 })
+        .select("*")
+        .build_query_as::<AuditEventRecord>()
+        .fetch_all(&state.db)
+        .await?;
+
+    if events.len() > EXPORT_MAX_ROWS as usize {
+        return Err(ApiError::ExportTooLarge(EXPORT_MAX_ROWS));
+    }
+
+    tracing::info!(admin = %claims.sub, rows = events.len(), "Registro de auditoria exportado");
+
+    let format = params.format.unwrap_or_default();
+    let (content_type, filename, body) = match format {
+        AuditExportFormat::Csv => ("text/csv; charset=utf-8", "audit-events.csv", to_csv(&events)),
+        AuditExportFormat::Ndjson => ("application/x-ndjson", "audit-events.ndjson", to_ndjson(&events)),
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        body,
+    ))
+}
+
+fn to_ndjson(events: &[AuditEventRecord]) -> String {
+    events
+        .iter()
+        .filter_map(|event| serde_json::to_string(event).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+fn to_csv(events: &[AuditEventRecord]) -> String {
+    let mut out = String::from("id,created_at,actor_id,actor_email,ip,action,entity_type,entity_id,before,after\n");
+    for event in events {
+        let json = |value: &Option<serde_json::Value>| value.as_ref().map(|v| v.to_string()).unwrap_or_default();
+        let fields = [
+            event.id.to_string(),
+            event.created_at.to_rfc3339(),
+            event.actor_id.map(|id| id.to_string()).unwrap_or_default(),
+            event.actor_email.clone(),
+            event.ip.clone(),
+            event.action.clone(),
+            event.entity_type.clone(),
+            event.entity_id.clone().unwrap_or_default(),
+            json(&event.before),
+            json(&event.after),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+// rfc 4180, ademas neutraliza formulas para que una hoja de calculo no las ejecute
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_csv_escapa_comillas_y_formulas() {
+        assert_eq!(csv_field("simple"), "simple");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("dice \"hola\""), "\"dice \"\"hola\"\"\"");
+        assert_eq!(csv_field("linea\nnueva"), "\"linea\nnueva\"");
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+    }
+
+    #[test]
+    fn test_filtros_de_auditoria_enlazados() {
+        use crate::services::listing::fixtures::assert_binds_hostile;
+
+        assert_binds_hostile(
+            |input| {
+                let params = AuditEventQuery {
+                    actor_id: Some(1),
+                    actor_email: Some(input.to_string()),
+                    action: Some(input.to_string()),
+                    entity_type: Some(input.to_string()),
+                    entity_id: Some(input.to_string()),
+                    from: Some(Utc::now()),
+                    to: Some(Utc::now()),
+                    page: None,
+                    limit: None,
+                    format: None,
+                };
+                audit_listing(&params).count().into_sql()
+            },
+            "SELECT COUNT(*) FROM audit_events WHERE actor_id = $1 AND lower(actor_email) = $2 \
+             AND action = $3 AND entity_type = $4 AND entity_id = $5 \
+             AND created_at >= $6 AND created_at < $7",
+        );
+    }
+
+    #[test]
+    fn test_csv_una_fila_por_evento() {
+        let event = AuditEventRecord {
+            id: 9,
+            actor_id: Some(1),
+            actor_email: "owner@labmedical.com".to_string(),
+            ip: "10.0.0.1".to_string(),
+            action: "product.update".to_string(),
+            entity_type: "product".to_string(),
+            entity_id: Some("4".to_string()),
+            before: Some(serde_json::json!({ "name": "Centrifuga, 12 tubos" })),
+            after: None,
+            created_at: Utc::now(),
+        };
+        let csv = to_csv(&[event]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("9,"));
+        assert!(lines[1].contains("\"{\"\"name\"\":\"\"Centrifuga, 12 tubos\"\"}\""));
+        assert!(lines[1].ends_with(','));
+    }
+}
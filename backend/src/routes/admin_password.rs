@@ -0,0 +1,198 @@
+use axum::{extract::State, routing::post, Json, Router};
+use chrono::{Duration, Utc};
+use validator::{Validate, ValidateArgs};
+
+use crate::{
+    error::{ApiError, ApiResult},
+    middleware::client_ip::ClientIp,
+    models::*,
+    services::{
+        audit::{self, Actor, AuditAction, AuditEvent},
+        auth::{generate_secure_token, hash_password, hash_token, verify_password, Claims},
+        login_guard, session,
+    },
+    AppState,
+};
+
+// cambio de contrasena con sesion iniciada, se monta dentro del router
+// autenticado de routes::admin
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/password", post(change_password))
+}
+
+async fn change_password(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> ApiResult<Json<LoginResponse>> {
+    payload.validate_with_args(&state.config.password_policy)
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let mut tx = state.db.begin().await?;
+
+    let admin = sqlx::query_as::<_, Admin>("SELECT * FROM admins WHERE id = $1 FOR UPDATE")
+        .bind(claims.admin_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+
+    if !verify_password(&payload.current_password, &admin.password_hash)? {
+        tracing::warn!(email = %admin.email, "Cambio de contrasena con contrasena actual incorrecta");
+        return Err(ApiError::Auth("Contrasena actual incorrecta".to_string()));
+    }
+
+    if verify_password(&payload.new_password, &admin.password_hash)? {
+        return Err(ApiError::BadRequest("La nueva contrasena debe ser distinta a la actual".to_string()));
+    }
+
+    sqlx::query("UPDATE admins SET password_hash = $1 WHERE id = $2")
+        .bind(hash_password(&payload.new_password, &state.config.password_hashing)?)
+        .bind(admin.id)
+        .execute(&mut *tx)
+        .await?;
+
+    // cierra las demas sesiones, quien cambio la contrasena recibe una nueva
+    session::revoke_all_sessions(&mut tx, admin.id).await?;
+    sqlx::query("UPDATE password_reset_tokens SET used_at = NOW() WHERE admin_id = $1 AND used_at IS NULL")
+        .bind(admin.id)
+        .execute(&mut *tx)
+        .await?;
+
+    let admin = sqlx::query_as::<_, Admin>("SELECT * FROM admins WHERE id = $1")
+        .bind(admin.id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    audit::record(&mut tx, &Actor::new(&claims, ip), AuditEvent::new(AuditAction::PasswordChange).entity(admin.id)).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin = %claims.sub, "Contrasena actualizada");
+
+    let response = session::issue_session(&state.db, &state.config, &state.jwt_keys, &admin).await?;
+    Ok(Json(response))
+}
+
+/// ruta publica, responde lo mismo exista o no el email para no revelar cuentas
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    payload.validate()
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    login_guard::check_burst(&state, ip)?;
+
+    // la busqueda y el envio corren fuera de la peticion para que el tiempo
+    // de respuesta tampoco delate si la cuenta existe
+    let email = payload.email.trim().to_lowercase();
+    tokio::spawn(async move {
+        if let Err(e) = send_reset_link(&state, &email).await {
+            tracing::error!(email = %email, "Error al enviar enlace de restablecimiento: {:?}", e);
+        }
+    });
+
+    Ok(Json(serde_json::json!({
+        "code": "OK",
+        "message": "Si el email corresponde a una cuenta activa, recibira un enlace para restablecer la contrasena"
+    })))
+}
+
+async fn send_reset_link(state: &AppState, email: &str) -> ApiResult<()> {
+    let admin: Option<(i32, String)> = sqlx::query_as(
+        "SELECT id, email FROM admins WHERE lower(email) = $1 AND is_active = true"
+    )
+    .bind(email)
+    .fetch_optional(&state.db)
+    .await?;
+
+    let Some((admin_id, admin_email)) = admin else {
+        tracing::warn!(email = %email, "Restablecimiento solicitado para email inexistente");
+        return Ok(());
+    };
+
+    let token = generate_secure_token();
+    let ttl_minutes = state.config.password_reset_ttl_minutes;
+
+    // un enlace nuevo invalida los anteriores del mismo admin
+    let mut tx = state.db.begin().await?;
+
+    sqlx::query("DELETE FROM password_reset_tokens WHERE admin_id = $1 AND used_at IS NULL")
+        .bind(admin_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO password_reset_tokens (admin_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        "#
+    )
+    .bind(admin_id)
+    .bind(hash_token(&token))
+    .bind(Utc::now() + Duration::minutes(ttl_minutes))
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let link = format!("{}/restablecer-contrasena?token={}", state.config.admin_panel_url, token);
+    state.email.send_password_reset(&admin_email, &link, ttl_minutes).await?;
+
+    tracing::info!(email = %admin_email, "Enlace de restablecimiento enviado");
+    Ok(())
+}
+
+/// ruta publica, el token del email es la unica credencial
+pub async fn reset_password(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    payload.validate_with_args(&state.config.password_policy)
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let mut tx = state.db.begin().await?;
+
+    // for update evita que dos peticiones simultaneas consuman el mismo token
+    let (token_id, admin_id, email): (i32, i32, String) = sqlx::query_as(
+        r#"
+        SELECT t.id, t.admin_id, a.email FROM password_reset_tokens t
+        JOIN admins a ON a.id = t.admin_id
+        WHERE t.token_hash = $1 AND t.used_at IS NULL AND t.expires_at > NOW()
+          AND a.is_active = true
+        FOR UPDATE OF t
+        "#
+    )
+    .bind(hash_token(&payload.token))
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::BadRequest("Enlace de restablecimiento invalido o expirado".to_string()))?;
+
+    sqlx::query("UPDATE admins SET password_hash = $1 WHERE id = $2")
+        .bind(hash_password(&payload.new_password, &state.config.password_hashing)?)
+        .bind(admin_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("UPDATE password_reset_tokens SET used_at = NOW() WHERE id = $1")
+        .bind(token_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // quien tuviera la contrasena anterior pierde sus sesiones
+    session::revoke_all_sessions(&mut tx, admin_id).await?;
+
+    // sin jwt, el actor es el propio titular del enlace
+    let actor = Actor { admin_id: Some(admin_id), email, ip };
+    audit::record(&mut tx, &actor, AuditEvent::new(AuditAction::PasswordReset).entity(admin_id)).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin_id = admin_id, "Contrasena restablecida por email");
+
+    Ok(Json(serde_json::json!({
+        "code": "OK",
+        "message": "Contrasena restablecida, ya puede iniciar sesion"
+    })))
+}
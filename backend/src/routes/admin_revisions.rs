@@ -0,0 +1,197 @@
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use sqlx::PgConnection;
+use validator::Validate;
+
+use crate::{
+    error::{ApiError, ApiResult, FieldErrors},
+    middleware::{
+        client_ip::ClientIp,
+        if_match::{etag, IfMatch},
+    },
+    models::*,
+    services::{
+        audit::{self, Actor, AuditAction, AuditEvent},
+        auth::Claims,
+        listing::{page_links, Filter, ListQuery, Pagination},
+        revisions::{self, RevisionAction},
+        suggest,
+        validation::field_errors,
+        workflow,
+    },
+    AppState,
+};
+
+use super::admin::{lock_product, write_product};
+
+// historial de revisiones de productos, se monta dentro del router
+// autenticado de routes::admin
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/products/:id/revisions", get(list_revisions))
+        .route("/products/:id/revisions/diff", get(diff_revisions))
+        .route("/products/:id/revisions/:version", get(get_revision))
+        .route("/products/:id/revisions/:version/restore", post(restore_revision))
+}
+
+// el historial sobrevive al producto: un producto eliminado sigue teniendo
+// sus revisiones para responder que se publico y cuando
+async fn list_revisions(
+    State(state): State<AppState>,
+    claims: Claims,
+    OriginalUri(uri): OriginalUri,
+    Path(product_id): Path<i32>,
+    Query(params): Query<RevisionQuery>,
+) -> ApiResult<(HeaderMap, Json<RevisionListResponse>)> {
+    claims.require(Permission::CatalogRead)?;
+
+    let pagination = Pagination::new(params.page, params.limit, 20, 100);
+    let listing = ListQuery::new("product_revisions").filter(Filter::eq("product_id", product_id));
+
+    let (total,): (i64,) = listing.count().build_query_as().fetch_one(&state.db).await?;
+    if total == 0 {
+        return Err(ApiError::NotFound("Producto no encontrado".to_string()));
+    }
+
+    let revisions = listing
+        .order_by("version DESC")
+        .paginate(pagination)
+        .select("id, product_id, version, action, restored_from, author_id, author_email, created_at")
+        .build_query_as::<RevisionSummary>()
+        .fetch_all(&state.db)
+        .await?;
+
+    let meta = PageMeta::new(pagination.page, pagination.limit, total);
+
+    Ok((page_links(&uri, &meta), Json(RevisionListResponse { revisions, meta })))
+}
+
+async fn get_revision(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((product_id, version)): Path<(i32, i32)>,
+) -> ApiResult<Json<ProductRevision>> {
+    claims.require(Permission::CatalogRead)?;
+
+    let mut conn = state.db.acquire().await?;
+    Ok(Json(find_revision(&mut conn, product_id, version).await?))
+}
+
+async fn diff_revisions(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(product_id): Path<i32>,
+    Query(params): Query<RevisionDiffQuery>,
+) -> ApiResult<Json<RevisionDiff>> {
+    claims.require(Permission::CatalogRead)?;
+
+    let mut conn = state.db.acquire().await?;
+    let to = match params.to {
+        Some(to) => to,
+        None => sqlx::query_scalar::<_, Option<i32>>(
+            "SELECT MAX(version) FROM product_revisions WHERE product_id = $1"
+        )
+        .bind(product_id)
+        .fetch_one(&mut *conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Producto no encontrado".to_string()))?,
+    };
+
+    let before = find_revision(&mut conn, product_id, params.from).await?;
+    let after = find_revision(&mut conn, product_id, to).await?;
+
+    Ok(Json(RevisionDiff {
+        product_id,
+        from: params.from,
+        to,
+        changes: revisions::diff(&before.snapshot, &after.snapshot),
+    }))
+}
+
+/// vuelve el producto al contenido de una revision anterior. no reescribe la
+/// historia: el resultado es una version nueva que recuerda de cual salio
+async fn restore_revision(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Path((product_id, version)): Path<(i32, i32)>,
+    if_match: IfMatch,
+) -> ApiResult<(HeaderMap, Json<Product>)> {
+    claims.require(Permission::CatalogWrite)?;
+
+    let mut tx = state.db.begin().await?;
+    let before = lock_product(&mut tx, product_id).await?;
+    claims.require(workflow::edit_permission(before.status))?;
+    if_match.check(before.version, &before)?;
+
+    let revision = find_revision(&mut tx, product_id, version).await?;
+    let fields: ProductFields = serde_json::from_value(revision.snapshot)
+        .map_err(|e| ApiError::Internal(format!("revision {} ilegible: {}", version, e)))?;
+
+    // la plantilla de la categoria no se exige: se restaura lo que se publico
+    // entonces aunque la plantilla haya cambiado despues
+    let mut errors = fields.validate().err().map(|e| field_errors(&e)).unwrap_or_default();
+    errors.extend(check_references(&mut tx, product_id, &fields).await?);
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let actor = Actor::new(&claims, ip);
+    let product = write_product(&mut tx, product_id, &fields).await?;
+    revisions::record(&mut tx, &actor, &product, RevisionAction::Restore, Some(version)).await?;
+    audit::record(
+        &mut tx,
+        &actor,
+        AuditEvent::new(AuditAction::ProductRestore)
+            .entity(product_id)
+            .before(&before)
+            .after(&product),
+    ).await?;
+    tx.commit().await?;
+    suggest::refresh(&state);
+
+    tracing::info!(admin = %claims.sub, product_id, restored_from = version, "Producto restaurado");
+    Ok((etag(product.version), Json(product)))
+}
+
+async fn find_revision(conn: &mut PgConnection, product_id: i32, version: i32) -> ApiResult<ProductRevision> {
+    sqlx::query_as::<_, ProductRevision>(
+        "SELECT * FROM product_revisions WHERE product_id = $1 AND version = $2"
+    )
+    .bind(product_id)
+    .bind(version)
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("Revision no encontrada".to_string()))
+}
+
+// lo que la revision referenciaba puede haber cambiado desde entonces: la
+// categoria pudo eliminarse y el slug pasar a otro producto
+async fn check_references(conn: &mut PgConnection, product_id: i32, fields: &ProductFields) -> ApiResult<FieldErrors> {
+    let mut errors = FieldErrors::new();
+
+    if let Some(category_id) = fields.category_id {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1)")
+            .bind(category_id)
+            .fetch_one(&mut *conn)
+            .await?;
+        if !exists {
+            errors.insert("category_id".to_string(), "Categoria no encontrada".to_string());
+        }
+    }
+
+    let taken: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM products WHERE slug = $1 AND id <> $2)")
+        .bind(&fields.slug)
+        .bind(product_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if taken {
+        errors.insert("slug".to_string(), "Slug en uso por otro producto".to_string());
+    }
+
+    Ok(errors)
+}
@@ -0,0 +1,413 @@
+use axum::{
+    extract::{OriginalUri, Query, State},
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use sqlx::PgConnection;
+use validator::Validate;
+
+use crate::{
+    error::{ApiError, ApiResult},
+    middleware::client_ip::ClientIp,
+    models::*,
+    services::{
+        audit::{self, Actor, AuditAction, AuditEvent},
+        auth::{hash_token, verify_password, Claims},
+        listing::{page_links, Filter, ListQuery, Pagination},
+        login_guard, session, totp,
+    },
+    AppState,
+};
+
+const TOTP_ISSUER: &str = "LabMedical";
+const RECOVERY_CODE_COUNT: usize = 10;
+const MFA_MAX_ATTEMPTS: i32 = 5;
+
+// rutas de 2fa y politica de seguridad, se montan dentro del router
+// autenticado de routes::admin
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/2fa/setup", post(setup_totp))
+        .route("/2fa/confirm", post(confirm_totp))
+        .route("/2fa/disable", post(disable_totp))
+        .route("/2fa/recovery-codes", post(regenerate_recovery_codes))
+        .route("/settings/security", get(get_security_settings).put(update_security_settings))
+        .route("/login-attempts", get(list_login_attempts))
+}
+
+/// ruta publica, segundo paso del login con el desafio emitido por /login
+pub async fn verify_mfa_login(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<VerifyMfaRequest>,
+) -> ApiResult<Json<LoginResponse>> {
+    payload.validate()
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let mut tx = state.db.begin().await?;
+
+    let (challenge_id, admin_id, attempts): (i32, i32, i32) = sqlx::query_as(
+        r#"
+        SELECT id, admin_id, attempts FROM admin_mfa_challenges
+        WHERE token_hash = $1 AND consumed_at IS NULL AND expires_at > NOW()
+        FOR UPDATE
+        "#
+    )
+    .bind(hash_token(&payload.challenge_token))
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::Auth("Desafio 2fa invalido o expirado".to_string()))?;
+
+    if attempts >= MFA_MAX_ATTEMPTS {
+        return Err(ApiError::Auth("Desafio 2fa agotado".to_string()));
+    }
+
+    let admin = sqlx::query_as::<_, Admin>("SELECT * FROM admins WHERE id = $1 FOR UPDATE")
+        .bind(admin_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .filter(|admin| admin.is_active && admin.totp_enabled)
+        .ok_or_else(|| ApiError::Auth("Administrador sin 2fa activo".to_string()))?;
+
+    // los fallos de 2fa cuentan para el mismo bloqueo que los de contrasena
+    let attempt_email = admin.email.trim().to_lowercase();
+    login_guard::check(&state, &attempt_email, ip).await?;
+
+    let accepted = match (&payload.code, &payload.recovery_code) {
+        (Some(code), _) => accept_totp_code(&mut tx, &admin, code).await?,
+        (None, Some(recovery_code)) => consume_recovery_code(&mut tx, admin.id, recovery_code).await?,
+        (None, None) => {
+            return Err(ApiError::BadRequest("Debe enviar code o recovery_code".to_string()));
+        }
+    };
+
+    if !accepted {
+        sqlx::query("UPDATE admin_mfa_challenges SET attempts = attempts + 1 WHERE id = $1")
+            .bind(challenge_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        login_guard::record(&state.db, &attempt_email, ip, false, "bad_mfa_code").await?;
+        tracing::warn!(email = %admin.email, "Codigo 2fa incorrecto");
+        return Err(ApiError::Auth("Codigo 2fa invalido".to_string()));
+    }
+
+    sqlx::query("UPDATE admin_mfa_challenges SET consumed_at = NOW() WHERE id = $1")
+        .bind(challenge_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("UPDATE admins SET last_login = NOW() WHERE id = $1")
+        .bind(admin.id)
+        .execute(&mut *tx)
+        .await?;
+
+    let method = if payload.code.is_some() { "totp" } else { "recovery_code" };
+    audit::record(
+        &mut tx,
+        &Actor::admin(&admin, ip),
+        AuditEvent::new(AuditAction::Login).entity(admin.id).after(&serde_json::json!({ "method": method })),
+    ).await?;
+
+    tx.commit().await?;
+
+    login_guard::record(&state.db, &attempt_email, ip, true, "mfa").await?;
+    let response = session::issue_session(&state.db, &state.config, &state.jwt_keys, &admin).await?;
+    tracing::info!(email = %admin.email, "login exitoso con 2fa");
+
+    Ok(Json(response))
+}
+
+async fn setup_totp(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> ApiResult<Json<TotpSetupResponse>> {
+    let admin = load_admin(&state, claims.admin_id).await?;
+    if admin.totp_enabled {
+        return Err(ApiError::BadRequest("La verificacion en dos pasos ya esta activa".to_string()));
+    }
+
+    // el secreto queda pendiente hasta que /2fa/confirm reciba un codigo valido
+    let secret = totp::generate_secret();
+    sqlx::query("UPDATE admins SET totp_secret = $1, totp_last_step = NULL WHERE id = $2")
+        .bind(&secret)
+        .bind(admin.id)
+        .execute(&state.db)
+        .await?;
+
+    Ok(Json(TotpSetupResponse {
+        provisioning_uri: totp::provisioning_uri(&secret, &admin.email, TOTP_ISSUER),
+        secret,
+    }))
+}
+
+async fn confirm_totp(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<TotpCodeRequest>,
+) -> ApiResult<Json<RecoveryCodesResponse>> {
+    payload.validate()
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let mut tx = state.db.begin().await?;
+    let admin = lock_admin(&mut tx, claims.admin_id).await?;
+
+    if admin.totp_enabled {
+        return Err(ApiError::BadRequest("La verificacion en dos pasos ya esta activa".to_string()));
+    }
+    if admin.totp_secret.is_none() {
+        return Err(ApiError::BadRequest("Primero debe iniciar la configuracion de 2FA".to_string()));
+    }
+    if !accept_totp_code(&mut tx, &admin, &payload.code).await? {
+        return Err(ApiError::BadRequest("Codigo 2FA invalido".to_string()));
+    }
+
+    sqlx::query("UPDATE admins SET totp_enabled = true WHERE id = $1")
+        .bind(admin.id)
+        .execute(&mut *tx)
+        .await?;
+
+    let recovery_codes = replace_recovery_codes(&mut tx, admin.id).await?;
+    audit::record(&mut tx, &Actor::new(&claims, ip), AuditEvent::new(AuditAction::TotpEnable).entity(admin.id)).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin = %claims.sub, "2fa activado");
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+async fn disable_totp(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<DisableTotpRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    payload.validate()
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    if load_settings(&state).await?.require_2fa {
+        return Err(ApiError::BadRequest(
+            "La politica de seguridad exige verificacion en dos pasos".to_string(),
+        ));
+    }
+
+    let mut tx = state.db.begin().await?;
+    let admin = lock_admin(&mut tx, claims.admin_id).await?;
+
+    if !admin.totp_enabled {
+        return Err(ApiError::BadRequest("La verificacion en dos pasos no esta activa".to_string()));
+    }
+    if !verify_password(&payload.password, &admin.password_hash)?
+        || !accept_totp_code(&mut tx, &admin, &payload.code).await?
+    {
+        return Err(ApiError::Auth("Contrasena o codigo 2fa invalido".to_string()));
+    }
+
+    sqlx::query(
+        "UPDATE admins SET totp_secret = NULL, totp_enabled = false, totp_last_step = NULL WHERE id = $1"
+    )
+    .bind(admin.id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM admin_recovery_codes WHERE admin_id = $1")
+        .bind(admin.id)
+        .execute(&mut *tx)
+        .await?;
+
+    audit::record(&mut tx, &Actor::new(&claims, ip), AuditEvent::new(AuditAction::TotpDisable).entity(admin.id)).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin = %claims.sub, "2fa desactivado");
+    Ok(Json(serde_json::json!({
+        "code": "OK",
+        "message": "Verificacion en dos pasos desactivada"
+    })))
+}
+
+async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<TotpCodeRequest>,
+) -> ApiResult<Json<RecoveryCodesResponse>> {
+    payload.validate()
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let mut tx = state.db.begin().await?;
+    let admin = lock_admin(&mut tx, claims.admin_id).await?;
+
+    if !admin.totp_enabled {
+        return Err(ApiError::BadRequest("La verificacion en dos pasos no esta activa".to_string()));
+    }
+    if !accept_totp_code(&mut tx, &admin, &payload.code).await? {
+        return Err(ApiError::BadRequest("Codigo 2FA invalido".to_string()));
+    }
+
+    let recovery_codes = replace_recovery_codes(&mut tx, admin.id).await?;
+    audit::record(&mut tx, &Actor::new(&claims, ip), AuditEvent::new(AuditAction::RecoveryCodesRegenerate).entity(admin.id)).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin = %claims.sub, "Codigos de recuperacion regenerados");
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+async fn get_security_settings(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> ApiResult<Json<SecuritySettings>> {
+    claims.require(Permission::ManageAdmins)?;
+
+    Ok(Json(load_settings(&state).await?))
+}
+
+async fn update_security_settings(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<UpdateSecuritySettingsRequest>,
+) -> ApiResult<Json<SecuritySettings>> {
+    claims.require(Permission::ManageAdmins)?;
+
+    let mut tx = state.db.begin().await?;
+
+    let before = sqlx::query_as::<_, SecuritySettings>(
+        "SELECT require_2fa, updated_at FROM security_settings WHERE id = 1 FOR UPDATE"
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let settings = sqlx::query_as::<_, SecuritySettings>(
+        r#"
+        UPDATE security_settings SET require_2fa = $1, updated_at = NOW()
+        WHERE id = 1
+        RETURNING require_2fa, updated_at
+        "#
+    )
+    .bind(payload.require_2fa)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    audit::record(
+        &mut tx,
+        &Actor::new(&claims, ip),
+        AuditEvent::new(AuditAction::SecuritySettingsUpdate).before(&before).after(&settings),
+    ).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin = %claims.sub, require_2fa = settings.require_2fa, "Politica de 2fa actualizada");
+    Ok(Json(settings))
+}
+
+async fn list_login_attempts(
+    State(state): State<AppState>,
+    claims: Claims,
+    OriginalUri(uri): OriginalUri,
+    Query(params): Query<LoginAttemptQuery>,
+) -> ApiResult<(HeaderMap, Json<LoginAttemptListResponse>)> {
+    claims.require(Permission::ManageAdmins)?;
+
+    let pagination = Pagination::new(params.page, params.limit, 50, 200);
+
+    // los mismos filtros se aplican al listado y al conteo
+    let listing = ListQuery::new("login_attempts")
+        .filter_opt(params.email.as_deref().map(|email| Filter::eq("email", email.trim().to_lowercase())))
+        .filter_opt(params.ip.as_deref().map(|ip| Filter::eq("ip", ip.trim())))
+        .filter_opt(params.success.map(|success| Filter::eq("success", success)));
+
+    let attempts = listing
+        .clone()
+        .order_by("created_at DESC")
+        .paginate(pagination)
+        .select("*")
+        .build_query_as::<LoginAttempt>()
+        .fetch_all(&state.db)
+        .await?;
+
+    let (total,): (i64,) = listing.count().build_query_as().fetch_one(&state.db).await?;
+
+    let meta = PageMeta::new(pagination.page, pagination.limit, total);
+
+    Ok((page_links(&uri, &meta), Json(LoginAttemptListResponse { attempts, meta })))
+}
+
+async fn load_settings(state: &AppState) -> ApiResult<SecuritySettings> {
+    Ok(sqlx::query_as::<_, SecuritySettings>(
+        "SELECT require_2fa, updated_at FROM security_settings WHERE id = 1"
+    )
+    .fetch_one(&state.db)
+    .await?)
+}
+
+async fn load_admin(state: &AppState, id: i32) -> ApiResult<Admin> {
+    sqlx::query_as::<_, Admin>("SELECT * FROM admins WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Administrador no encontrado".to_string()))
+}
+
+async fn lock_admin(conn: &mut PgConnection, id: i32) -> ApiResult<Admin> {
+    sqlx::query_as::<_, Admin>("SELECT * FROM admins WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Administrador no encontrado".to_string()))
+}
+
+// valida el codigo y registra el intervalo usado, la fila del admin debe
+// estar bloqueada por la transaccion que llama
+async fn accept_totp_code(conn: &mut PgConnection, admin: &Admin, code: &str) -> ApiResult<bool> {
+    let Some(secret) = admin.totp_secret.as_deref() else {
+        return Ok(false);
+    };
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    let Some(step) = totp::verify_code(secret, code, now, admin.totp_last_step) else {
+        return Ok(false);
+    };
+
+    sqlx::query("UPDATE admins SET totp_last_step = $1 WHERE id = $2")
+        .bind(step)
+        .bind(admin.id)
+        .execute(conn)
+        .await?;
+
+    Ok(true)
+}
+
+async fn consume_recovery_code(conn: &mut PgConnection, admin_id: i32, code: &str) -> ApiResult<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE admin_recovery_codes SET used_at = NOW()
+        WHERE admin_id = $1 AND code_hash = $2 AND used_at IS NULL
+        "#
+    )
+    .bind(admin_id)
+    .bind(hash_token(&totp::normalize_recovery_code(code)))
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+async fn replace_recovery_codes(conn: &mut PgConnection, admin_id: i32) -> ApiResult<Vec<String>> {
+    let codes = totp::generate_recovery_codes(RECOVERY_CODE_COUNT);
+    let hashes: Vec<String> = codes.iter().map(|code| hash_token(code)).collect();
+
+    sqlx::query("DELETE FROM admin_recovery_codes WHERE admin_id = $1")
+        .bind(admin_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO admin_recovery_codes (admin_id, code_hash) SELECT $1, UNNEST($2::text[])"
+    )
+    .bind(admin_id)
+    .bind(&hashes)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(codes)
+}
@@ -0,0 +1,443 @@
+use axum::{
+    extract::{Path, State},
+    routing::{get, put},
+    Json, Router,
+};
+use sqlx::PgConnection;
+use validator::Validate;
+
+use crate::{
+    error::{ApiError, ApiResult, FieldErrors},
+    middleware::client_ip::ClientIp,
+    models::*,
+    services::{
+        audit::{self, Actor, AuditAction, AuditEvent},
+        auth::Claims,
+        specs,
+        validation::{field_errors, sanitize_text},
+    },
+    AppState,
+};
+
+// campos por plantilla, el formulario de producto se vuelve inmanejable antes
+const MAX_TEMPLATE_FIELDS: usize = 100;
+
+// definiciones de especificaciones por categoria, se montan dentro del
+// router autenticado de routes::admin
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/categories/:id/specs", get(list_spec_definitions).post(create_spec_definition))
+        .route("/categories/:id/specs/:key", put(update_spec_definition).delete(delete_spec_definition))
+        .route("/categories/:id/spec-template", get(get_spec_template).put(update_spec_template))
+}
+
+async fn list_spec_definitions(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(category_id): Path<i32>,
+) -> ApiResult<Json<Vec<SpecDefinition>>> {
+    claims.require(Permission::CatalogRead)?;
+
+    let mut conn = state.db.acquire().await?;
+    ensure_category(&mut conn, category_id).await?;
+
+    Ok(Json(specs::for_category(&mut *conn, category_id).await?))
+}
+
+async fn create_spec_definition(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Path(category_id): Path<i32>,
+    Json(payload): Json<CreateSpecDefinitionRequest>,
+) -> ApiResult<Json<SpecDefinition>> {
+    claims.require(Permission::CatalogWrite)?;
+
+    let options = check_definition(&payload).map_err(ApiError::InvalidFields)?;
+
+    let mut tx = state.db.begin().await?;
+    ensure_category(&mut tx, category_id).await?;
+
+    let (exists,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS(SELECT 1 FROM category_spec_definitions WHERE category_id = $1 AND key = $2)"
+    )
+    .bind(category_id)
+    .bind(&payload.key)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if exists {
+        return Err(ApiError::BadRequest("La categoria ya tiene una especificacion con esa clave".to_string()));
+    }
+
+    let definition = sqlx::query_as::<_, SpecDefinition>(
+        r#"
+        INSERT INTO category_spec_definitions (
+            category_id, key, label, unit, data_type, section, required,
+            options, min_value, max_value, filterable, position
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        RETURNING *
+        "#
+    )
+    .bind(category_id)
+    .bind(&payload.key)
+    .bind(sanitize_text(&payload.label))
+    .bind(payload.unit.as_deref().map(sanitize_text))
+    .bind(payload.data_type.as_str())
+    .bind(payload.section.as_str())
+    .bind(payload.required)
+    .bind(&options)
+    .bind(payload.min_value)
+    .bind(payload.max_value)
+    .bind(payload.filterable)
+    .bind(payload.position)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    audit::record(
+        &mut tx,
+        &Actor::new(&claims, ip),
+        AuditEvent::new(AuditAction::SpecDefinitionCreate).entity(category_id).after(&definition),
+    ).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin = %claims.sub, category_id, key = %definition.key, "Especificacion creada");
+    Ok(Json(definition))
+}
+
+// la clave, el tipo y la seccion no cambian, los valores guardados en los
+// productos dependen de ellos. para cambiarlos se elimina y se crea otra
+async fn update_spec_definition(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Path((category_id, key)): Path<(i32, String)>,
+    Json(payload): Json<UpdateSpecDefinitionRequest>,
+) -> ApiResult<Json<SpecDefinition>> {
+    claims.require(Permission::CatalogWrite)?;
+
+    let mut tx = state.db.begin().await?;
+
+    let before = sqlx::query_as::<_, SpecDefinition>(
+        "SELECT * FROM category_spec_definitions WHERE category_id = $1 AND key = $2 FOR UPDATE"
+    )
+    .bind(category_id)
+    .bind(&key)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("Especificacion no encontrada".to_string()))?;
+
+    // se valida como la definicion completa que quedara guardada
+    let changes = CreateSpecDefinitionRequest {
+        key: before.key.clone(),
+        label: payload.label,
+        unit: payload.unit,
+        data_type: before.data_type,
+        section: before.section,
+        required: payload.required,
+        options: payload.options,
+        min_value: payload.min_value,
+        max_value: payload.max_value,
+        filterable: payload.filterable,
+        position: payload.position,
+    };
+    let options = check_definition(&changes).map_err(ApiError::InvalidFields)?;
+
+    let definition = sqlx::query_as::<_, SpecDefinition>(
+        r#"
+        UPDATE category_spec_definitions
+        SET label = $1,
+            unit = $2,
+            required = $3,
+            options = $4,
+            min_value = $5,
+            max_value = $6,
+            filterable = $7,
+            position = $8,
+            updated_at = NOW()
+        WHERE id = $9
+        RETURNING *
+        "#
+    )
+    .bind(sanitize_text(&changes.label))
+    .bind(changes.unit.as_deref().map(sanitize_text))
+    .bind(changes.required)
+    .bind(&options)
+    .bind(changes.min_value)
+    .bind(changes.max_value)
+    .bind(changes.filterable)
+    .bind(changes.position)
+    .bind(before.id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    audit::record(
+        &mut tx,
+        &Actor::new(&claims, ip),
+        AuditEvent::new(AuditAction::SpecDefinitionUpdate).entity(category_id).before(&before).after(&definition),
+    ).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin = %claims.sub, category_id, key = %key, "Especificacion actualizada");
+    Ok(Json(definition))
+}
+
+// los valores en products.specifications se conservan, solo dejan de
+// validarse y filtrarse
+async fn delete_spec_definition(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Path((category_id, key)): Path<(i32, String)>,
+) -> ApiResult<Json<serde_json::Value>> {
+    claims.require(Permission::CatalogWrite)?;
+
+    let mut tx = state.db.begin().await?;
+
+    let deleted = sqlx::query_as::<_, SpecDefinition>(
+        "DELETE FROM category_spec_definitions WHERE category_id = $1 AND key = $2 RETURNING *"
+    )
+    .bind(category_id)
+    .bind(&key)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("Especificacion no encontrada".to_string()))?;
+
+    audit::record(
+        &mut tx,
+        &Actor::new(&claims, ip),
+        AuditEvent::new(AuditAction::SpecDefinitionDelete).entity(category_id).before(&deleted),
+    ).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin = %claims.sub, category_id, key = %key, "Especificacion eliminada");
+    Ok(Json(serde_json::json!({
+        "code": "OK",
+        "message": "Especificacion eliminada exitosamente"
+    })))
+}
+
+async fn ensure_category(conn: &mut PgConnection, category_id: i32) -> ApiResult<()> {
+    let (exists,): (bool,) = sqlx::query_as("SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1)")
+        .bind(category_id)
+        .fetch_one(conn)
+        .await?;
+
+    if exists {
+        Ok(())
+    } else {
+        Err(ApiError::NotFound("Categoria no encontrada".to_string()))
+    }
+}
+
+async fn get_spec_template(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(category_id): Path<i32>,
+) -> ApiResult<Json<serde_json::Value>> {
+    claims.require(Permission::CatalogRead)?;
+
+    let mut conn = state.db.acquire().await?;
+    let template = specs::template(&mut conn, category_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Categoria no encontrada".to_string()))?;
+
+    Ok(Json(template.schema()))
+}
+
+// reemplaza todas las definiciones de la categoria. las claves que siguen
+// conservan id y fecha de creacion, las que faltan se eliminan
+async fn update_spec_template(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Path(category_id): Path<i32>,
+    Json(payload): Json<UpdateSpecTemplateRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    claims.require(Permission::CatalogWrite)?;
+
+    if payload.fields.len() > MAX_TEMPLATE_FIELDS {
+        return Err(ApiError::Validation(format!(
+            "La plantilla admite hasta {} campos", MAX_TEMPLATE_FIELDS
+        )));
+    }
+
+    let mut errors = FieldErrors::new();
+    let mut options = Vec::with_capacity(payload.fields.len());
+    for (i, field) in payload.fields.iter().enumerate() {
+        match check_definition(field) {
+            Ok(field_options) => options.push(field_options),
+            Err(field_errors) => errors.extend(
+                field_errors.into_iter().map(|(name, message)| (format!("fields[{}].{}", i, name), message)),
+            ),
+        }
+        if payload.fields[..i].iter().any(|previous| previous.key == field.key) {
+            errors.insert(format!("fields[{}].key", i), "Clave repetida en la plantilla".to_string());
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    let before = specs::template(&mut tx, category_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Categoria no encontrada".to_string()))?;
+
+    sqlx::query("UPDATE categories SET strict_specs = $1, version = version + 1 WHERE id = $2")
+        .bind(payload.strict)
+        .bind(category_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let keys: Vec<&str> = payload.fields.iter().map(|field| field.key.as_str()).collect();
+    sqlx::query("DELETE FROM category_spec_definitions WHERE category_id = $1 AND key <> ALL($2)")
+        .bind(category_id)
+        .bind(&keys)
+        .execute(&mut *tx)
+        .await?;
+
+    for (field, field_options) in payload.fields.iter().zip(&options) {
+        sqlx::query(
+            r#"
+            INSERT INTO category_spec_definitions (
+                category_id, key, label, unit, data_type, section, required,
+                options, min_value, max_value, filterable, position
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (category_id, key) DO UPDATE
+            SET label = EXCLUDED.label,
+                unit = EXCLUDED.unit,
+                data_type = EXCLUDED.data_type,
+                section = EXCLUDED.section,
+                required = EXCLUDED.required,
+                options = EXCLUDED.options,
+                min_value = EXCLUDED.min_value,
+                max_value = EXCLUDED.max_value,
+                filterable = EXCLUDED.filterable,
+                position = EXCLUDED.position,
+                updated_at = NOW()
+            "#
+        )
+        .bind(category_id)
+        .bind(&field.key)
+        .bind(sanitize_text(&field.label))
+        .bind(field.unit.as_deref().map(sanitize_text))
+        .bind(field.data_type.as_str())
+        .bind(field.section.as_str())
+        .bind(field.required)
+        .bind(field_options)
+        .bind(field.min_value)
+        .bind(field.max_value)
+        .bind(field.filterable)
+        .bind(field.position)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let after = specs::template(&mut tx, category_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Categoria no encontrada".to_string()))?;
+    let schema = after.schema();
+
+    audit::record(
+        &mut tx,
+        &Actor::new(&claims, ip),
+        AuditEvent::new(AuditAction::SpecTemplateUpdate).entity(category_id).before(&before.schema()).after(&schema),
+    ).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin = %claims.sub, category_id, campos = keys.len(), "Plantilla de especificaciones actualizada");
+    Ok(Json(schema))
+}
+
+// formato de la definicion y coherencia entre tipo, opciones y limites.
+// devuelve las opciones sin espacios sobrantes ni repetidas, que no pasan por
+// sanitize_text porque se comparan tal cual con los valores del producto
+fn check_definition(definition: &CreateSpecDefinitionRequest) -> Result<Vec<String>, FieldErrors> {
+    let mut errors = definition.validate().err().map(|e| field_errors(&e)).unwrap_or_default();
+
+    let mut options: Vec<String> = Vec::new();
+    for option in definition.options.iter().map(|o| o.trim().to_string()) {
+        if option.is_empty() || option.chars().count() > 100 {
+            errors.insert("options".to_string(), "Cada opcion debe tener entre 1 y 100 caracteres".to_string());
+        } else if !options.contains(&option) {
+            options.push(option);
+        }
+    }
+
+    match definition.data_type {
+        SpecDataType::Enum if options.is_empty() => {
+            errors.entry("options".to_string()).or_insert_with(|| "Una especificacion enum requiere opciones".to_string());
+        }
+        SpecDataType::Enum => {}
+        _ if !options.is_empty() => {
+            errors.insert("options".to_string(), "Solo las especificaciones enum admiten opciones".to_string());
+        }
+        _ => {}
+    }
+
+    let bounds = definition.min_value.is_some() || definition.max_value.is_some();
+    if bounds && definition.data_type != SpecDataType::Number {
+        errors.insert("min_value".to_string(), "Solo las especificaciones number admiten limites".to_string());
+    } else if [definition.min_value, definition.max_value].into_iter().flatten().any(|bound| !bound.is_finite()) {
+        errors.insert("min_value".to_string(), "Los limites deben ser numeros finitos".to_string());
+    } else if let (Some(min), Some(max)) = (definition.min_value, definition.max_value) {
+        if min > max {
+            errors.insert("max_value".to_string(), "Debe ser mayor o igual a min_value".to_string());
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(options)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(data_type: SpecDataType, options: &[&str]) -> CreateSpecDefinitionRequest {
+        CreateSpecDefinitionRequest {
+            key: "modo".to_string(),
+            label: "Modo".to_string(),
+            unit: None,
+            data_type,
+            section: SpecSection::Specifications,
+            required: false,
+            options: options.iter().map(|o| o.to_string()).collect(),
+            min_value: None,
+            max_value: None,
+            filterable: true,
+            position: 0,
+        }
+    }
+
+    #[test]
+    fn test_opciones_solo_para_enum() {
+        let options = [" adulto ", "neonatal", "adulto"];
+        assert_eq!(check_definition(&field(SpecDataType::Enum, &options)).unwrap(), vec!["adulto", "neonatal"]);
+        assert!(check_definition(&field(SpecDataType::Enum, &[])).unwrap_err().contains_key("options"));
+        assert!(check_definition(&field(SpecDataType::Enum, &["  "])).unwrap_err().contains_key("options"));
+        assert!(check_definition(&field(SpecDataType::Number, &options)).unwrap_err().contains_key("options"));
+        assert!(check_definition(&field(SpecDataType::Number, &[])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_limites_y_clave_por_campo() {
+        let mut number = field(SpecDataType::Number, &[]);
+        number.min_value = Some(10.0);
+        number.max_value = Some(1.0);
+        number.key = "Peso-kg".to_string();
+        let errors = check_definition(&number).unwrap_err();
+        assert_eq!(errors.keys().collect::<Vec<_>>(), vec!["key", "max_value"]);
+
+        let mut text = field(SpecDataType::Text, &[]);
+        text.max_value = Some(1.0);
+        assert!(check_definition(&text).unwrap_err().contains_key("min_value"));
+    }
+}
@@ -0,0 +1,184 @@
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use sqlx::PgConnection;
+
+use crate::{
+    error::{ApiError, ApiResult, FieldErrors},
+    middleware::{
+        client_ip::ClientIp,
+        if_match::{etag, IfMatch},
+    },
+    models::*,
+    services::{
+        audit::{self, Actor, AuditAction, AuditEvent},
+        auth::Claims,
+        listing::{page_links, Filter, ListQuery, Pagination},
+        revisions::{self, RevisionAction},
+        suggest, trash, workflow,
+    },
+    AppState,
+};
+
+// papelera de productos y categorias, se monta dentro del router autenticado
+// de routes::admin. la purga programada esta en services::trash
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/products/trash", get(list_trashed_products))
+        .route("/products/:id/restore", post(restore_product))
+        .route("/categories/trash", get(list_trashed_categories))
+        .route("/categories/:id/restore", post(restore_category))
+}
+
+async fn list_trashed_products(
+    State(state): State<AppState>,
+    claims: Claims,
+    OriginalUri(uri): OriginalUri,
+    Query(params): Query<TrashQuery>,
+) -> ApiResult<(HeaderMap, Json<TrashedProductListResponse>)> {
+    claims.require(Permission::CatalogRead)?;
+
+    let pagination = Pagination::new(params.page, params.limit, 50, 100);
+    let listing = ListQuery::new("products").filter(Filter::sql("deleted_at IS NOT NULL"));
+
+    let mut products = listing
+        .clone()
+        .order_by("deleted_at DESC, id DESC")
+        .paginate(pagination)
+        .select("*, EXISTS(SELECT 1 FROM quotes WHERE products.id = ANY(quotes.product_ids)) AS quoted")
+        .build_query_as::<TrashedProduct>()
+        .fetch_all(&state.db)
+        .await?;
+
+    let (total,): (i64,) = listing.count().build_query_as().fetch_one(&state.db).await?;
+
+    for trashed in &mut products {
+        trashed.purge_at = trash::purge_at(trashed.product.deleted_at, trashed.quoted, state.config.trash_retention_days);
+    }
+
+    let meta = PageMeta::new(pagination.page, pagination.limit, total);
+
+    Ok((page_links(&uri, &meta), Json(TrashedProductListResponse { products, meta })))
+}
+
+async fn list_trashed_categories(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> ApiResult<Json<Vec<TrashedCategory>>> {
+    claims.require(Permission::CatalogRead)?;
+
+    let mut categories = sqlx::query_as::<_, TrashedCategory>(
+        "SELECT *, EXISTS(SELECT 1 FROM products \
+         WHERE products.category_id = categories.id AND products.deleted_at IS NULL) AS has_products \
+         FROM categories WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC, id DESC"
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    for trashed in &mut categories {
+        trashed.purge_at = trash::purge_at(trashed.category.deleted_at, trashed.has_products, state.config.trash_retention_days);
+    }
+
+    Ok(Json(categories))
+}
+
+async fn restore_product(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Path(id): Path<i32>,
+    if_match: IfMatch,
+) -> ApiResult<(HeaderMap, Json<Product>)> {
+    claims.require(Permission::CatalogWrite)?;
+
+    let mut tx = state.db.begin().await?;
+    let before = lock_trashed_product(&mut tx, id).await?;
+    // un publicado vuelve directo al sitio, lo decide el aprobador
+    claims.require(workflow::edit_permission(before.status))?;
+    if_match.check(before.version, &before)?;
+
+    // volveria oculto del sitio publico sin que nadie lo note
+    let category_trashed: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND deleted_at IS NOT NULL)"
+    )
+    .bind(before.category_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if category_trashed {
+        let mut errors = FieldErrors::new();
+        errors.insert("category_id".to_string(), "Categoria en la papelera, recuperela primero".to_string());
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let product = sqlx::query_as::<_, Product>(
+        "UPDATE products SET deleted_at = NULL, version = version + 1, updated_at = NOW() \
+         WHERE id = $1 RETURNING *"
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let actor = Actor::new(&claims, ip);
+    revisions::record(&mut tx, &actor, &product, RevisionAction::Undelete, None).await?;
+    audit::record(
+        &mut tx,
+        &actor,
+        AuditEvent::new(AuditAction::ProductUndelete).entity(id).before(&before).after(&product),
+    ).await?;
+    tx.commit().await?;
+    suggest::refresh(&state);
+
+    tracing::info!(admin = %claims.sub, product_id = id, "Producto recuperado de la papelera");
+    Ok((etag(product.version), Json(product)))
+}
+
+async fn restore_category(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Path(id): Path<i32>,
+    if_match: IfMatch,
+) -> ApiResult<(HeaderMap, Json<Category>)> {
+    claims.require(Permission::CatalogWrite)?;
+
+    let mut tx = state.db.begin().await?;
+    let before = lock_trashed_category(&mut tx, id).await?;
+    if_match.check(before.version, &before)?;
+
+    let category = sqlx::query_as::<_, Category>(
+        "UPDATE categories SET deleted_at = NULL, version = version + 1 WHERE id = $1 RETURNING *"
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    audit::record(
+        &mut tx,
+        &Actor::new(&claims, ip),
+        AuditEvent::new(AuditAction::CategoryUndelete).entity(id).before(&before).after(&category),
+    ).await?;
+    tx.commit().await?;
+    suggest::refresh(&state);
+
+    tracing::info!(admin = %claims.sub, category_id = id, "Categoria recuperada de la papelera");
+    Ok((etag(category.version), Json(category)))
+}
+
+async fn lock_trashed_product(tx: &mut PgConnection, id: i32) -> ApiResult<Product> {
+    sqlx::query_as::<_, Product>("SELECT * FROM products WHERE id = $1 AND deleted_at IS NOT NULL FOR UPDATE")
+        .bind(id)
+        .fetch_optional(tx)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Producto no encontrado en la papelera".to_string()))
+}
+
+async fn lock_trashed_category(tx: &mut PgConnection, id: i32) -> ApiResult<Category> {
+    sqlx::query_as::<_, Category>("SELECT * FROM categories WHERE id = $1 AND deleted_at IS NOT NULL FOR UPDATE")
+        .bind(id)
+        .fetch_optional(tx)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Categoria no encontrada en la papelera".to_string()))
+}
@@ -0,0 +1,312 @@
+use axum::{
+    extract::{Path, State},
+    routing::{delete, get, patch, post},
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+use sqlx::{Postgres, Transaction};
+use validator::{Validate, ValidateArgs};
+
+use crate::{
+    error::{ApiError, ApiResult},
+    middleware::client_ip::ClientIp,
+    models::*,
+    services::{
+        audit::{self, Actor, AuditAction, AuditEvent},
+        auth::{generate_secure_token, hash_password, hash_token, Claims},
+        session,
+        validation::sanitize_text,
+    },
+    AppState,
+};
+
+// rutas de gestion de administradores, se montan dentro del router
+// autenticado de routes::admin
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admins", get(list_admins))
+        .route("/admins/invitations", post(invite_admin))
+        .route("/admins/:id", delete(delete_admin))
+        .route("/admins/:id/status", patch(update_admin_status))
+}
+
+async fn list_admins(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> ApiResult<Json<Vec<Admin>>> {
+    claims.require(Permission::ManageAdmins)?;
+
+    let admins = sqlx::query_as::<_, Admin>(
+        "SELECT * FROM admins ORDER BY created_at ASC"
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(admins))
+}
+
+async fn invite_admin(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<InviteAdminRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    claims.require(Permission::ManageAdmins)?;
+
+    payload.validate()
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    // los emails se guardan en minusculas, "Ventas@" y "ventas@" son la misma cuenta
+    let email = payload.email.trim().to_lowercase();
+
+    let (exists,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS(SELECT 1 FROM admins WHERE lower(email) = $1)"
+    )
+    .bind(&email)
+    .fetch_one(&state.db)
+    .await?;
+
+    if exists {
+        return Err(ApiError::BadRequest("Ya existe un administrador con ese email".to_string()));
+    }
+
+    let token = generate_secure_token();
+    let expires_at = Utc::now() + Duration::hours(state.config.invitation_ttl_hours);
+
+    // una invitacion nueva reemplaza a las pendientes del mismo email
+    let mut tx = state.db.begin().await?;
+
+    sqlx::query("DELETE FROM admin_invitations WHERE lower(email) = $1 AND accepted_at IS NULL")
+        .bind(&email)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO admin_invitations (email, name, role, token_hash, invited_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#
+    )
+    .bind(&email)
+    .bind(payload.name.as_deref().map(sanitize_text))
+    .bind(payload.role.as_str())
+    .bind(hash_token(&token))
+    .bind(claims.admin_id)
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await?;
+
+    audit::record(
+        &mut tx,
+        &Actor::new(&claims, ip),
+        AuditEvent::new(AuditAction::AdminInvite)
+            .entity(&email)
+            .after(&serde_json::json!({ "email": email, "role": payload.role, "expires_at": expires_at })),
+    ).await?;
+    tx.commit().await?;
+
+    let link = format!("{}/aceptar-invitacion?token={}", state.config.admin_panel_url, token);
+    state.email.send_admin_invitation(
+        &email,
+        &claims.sub,
+        payload.role.as_str(),
+        &link,
+        state.config.invitation_ttl_hours,
+    ).await?;
+
+    tracing::info!(admin = %claims.sub, invited = %email, role = %payload.role, "Invitacion de administrador enviada");
+
+    Ok(Json(serde_json::json!({
+        "code": "OK",
+        "message": "Invitacion enviada exitosamente",
+        "expires_at": expires_at,
+    })))
+}
+
+/// ruta publica, el token de la invitacion es la unica credencial
+pub async fn accept_invitation(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<AcceptInvitationRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    payload.validate_with_args(&state.config.password_policy)
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let mut tx = state.db.begin().await?;
+
+    // for update evita que dos peticiones simultaneas consuman la misma invitacion
+    let invitation = sqlx::query_as::<_, AdminInvitation>(
+        r#"
+        SELECT id, email, name, role FROM admin_invitations
+        WHERE token_hash = $1 AND accepted_at IS NULL AND expires_at > NOW()
+        FOR UPDATE
+        "#
+    )
+    .bind(hash_token(&payload.token))
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::BadRequest("Invitacion invalida o expirada".to_string()))?;
+
+    // invitaciones anteriores pueden traer mayusculas, la unicidad de la
+    // columna no distingue "Ventas@" de "ventas@"
+    let (exists,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS(SELECT 1 FROM admins WHERE lower(email) = lower($1))"
+    )
+    .bind(&invitation.email)
+    .fetch_one(&mut *tx)
+    .await?;
+    if exists {
+        return Err(ApiError::BadRequest("Ya existe un administrador con ese email".to_string()));
+    }
+
+    let password_hash = hash_password(&payload.password, &state.config.password_hashing)?;
+    let name = payload.name.as_deref().map(sanitize_text).or(invitation.name);
+    let email = invitation.email.to_lowercase();
+
+    let inserted: Option<(i32,)> = sqlx::query_as(
+        r#"
+        INSERT INTO admins (email, password_hash, name, role)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (email) DO NOTHING
+        RETURNING id
+        "#
+    )
+    .bind(&email)
+    .bind(&password_hash)
+    .bind(&name)
+    .bind(invitation.role.as_str())
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((admin_id,)) = inserted else {
+        return Err(ApiError::BadRequest("Ya existe un administrador con ese email".to_string()));
+    };
+
+    sqlx::query("UPDATE admin_invitations SET accepted_at = NOW() WHERE id = $1")
+        .bind(invitation.id)
+        .execute(&mut *tx)
+        .await?;
+
+    // sin jwt todavia, el actor es la cuenta recien creada
+    let actor = Actor { admin_id: Some(admin_id), email: email.clone(), ip };
+    audit::record(
+        &mut tx,
+        &actor,
+        AuditEvent::new(AuditAction::AdminCreate)
+            .entity(admin_id)
+            .after(&serde_json::json!({ "email": email, "name": name, "role": invitation.role, "invitation_id": invitation.id })),
+    ).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin_id = admin_id, email = %invitation.email, "Invitacion aceptada");
+
+    Ok(Json(serde_json::json!({
+        "code": "OK",
+        "message": "Cuenta creada exitosamente, ya puede iniciar sesion"
+    })))
+}
+
+async fn update_admin_status(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Path(id): Path<i32>,
+    Json(payload): Json<UpdateAdminStatusRequest>,
+) -> ApiResult<Json<Admin>> {
+    claims.require(Permission::ManageAdmins)?;
+
+    if id == claims.admin_id && !payload.is_active {
+        return Err(ApiError::BadRequest("No puede desactivar su propia cuenta".to_string()));
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    if !payload.is_active {
+        ensure_not_last_owner(&mut tx, id).await?;
+    }
+
+    let admin = sqlx::query_as::<_, Admin>(
+        "UPDATE admins SET is_active = $1 WHERE id = $2 RETURNING *"
+    )
+    .bind(payload.is_active)
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("Administrador no encontrado".to_string()))?;
+
+    // los tokens ya emitidos dejan de ser validos de inmediato
+    if !admin.is_active {
+        session::revoke_all_sessions(&mut tx, admin.id).await?;
+    }
+
+    audit::record(
+        &mut tx,
+        &Actor::new(&claims, ip),
+        AuditEvent::new(AuditAction::AdminStatusUpdate)
+            .entity(admin.id)
+            .after(&serde_json::json!({ "email": admin.email, "is_active": admin.is_active })),
+    ).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        admin = %claims.sub,
+        target = %admin.email,
+        is_active = admin.is_active,
+        "Estado de administrador actualizado"
+    );
+    Ok(Json(admin))
+}
+
+async fn delete_admin(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<serde_json::Value>> {
+    claims.require(Permission::ManageAdmins)?;
+
+    if id == claims.admin_id {
+        return Err(ApiError::BadRequest("No puede eliminar su propia cuenta".to_string()));
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    ensure_not_last_owner(&mut tx, id).await?;
+
+    let deleted = sqlx::query_as::<_, Admin>("DELETE FROM admins WHERE id = $1 RETURNING *")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Administrador no encontrado".to_string()))?;
+
+    audit::record(
+        &mut tx,
+        &Actor::new(&claims, ip),
+        AuditEvent::new(AuditAction::AdminDelete).entity(id).before(&deleted),
+    ).await?;
+    tx.commit().await?;
+
+    tracing::info!(admin = %claims.sub, target_id = id, "Administrador eliminado");
+    Ok(Json(serde_json::json!({
+        "code": "OK",
+        "message": "Administrador eliminado exitosamente"
+    })))
+}
+
+// bloquea las filas de owners activos para que dos operaciones concurrentes
+// no puedan dejar el panel sin ningun owner
+async fn ensure_not_last_owner(tx: &mut Transaction<'_, Postgres>, id: i32) -> ApiResult<()> {
+    let owners: Vec<(i32,)> = sqlx::query_as(
+        "SELECT id FROM admins WHERE role = 'owner' AND is_active = true FOR UPDATE"
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    if owners.len() == 1 && owners[0].0 == id {
+        return Err(ApiError::BadRequest(
+            "No se puede eliminar ni desactivar el ultimo owner".to_string(),
+        ));
+    }
+
+    Ok(())
+}
@@ -1,2 +1,9 @@
 pub mod public;
-pub mod admin;
\ No newline at end of file
+pub mod admin;
+pub mod admin_audit;
+pub mod admin_password;
+pub mod admin_users;
+pub mod admin_security;
+pub mod admin_specs;
+pub mod admin_revisions;
+pub mod admin_trash;
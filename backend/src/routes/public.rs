@@ -1,92 +1,363 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{OriginalUri, Path, State},
+    http::{header, HeaderMap},
+    response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
+use axum_extra::extract::Query;
 use serde::Deserialize;
 use validator::Validate;
 
 use crate::{
     error::{ApiError, ApiResult},
     models::*,
-    services::validation::{validate_ruc, sanitize_text},
+    services::{
+        compare,
+        listing::{cursor_links, page_links, Filter, ListQuery, Pagination},
+        product_sort::ProductSort,
+        search,
+        specs::{self, SpecCondition, SpecFilter, MAX_SPEC_FILTERS},
+        validation::{validate_ruc, sanitize_text},
+        visibility,
+    },
     AppState,
 };
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/products", get(get_products))
+        .route("/products/compare", get(compare_products))
         .route("/products/:slug", get(get_product_by_slug))
         .route("/categories", get(get_categories))
+        .route("/categories/:slug/specs", get(get_category_specs))
+        .route("/search/suggest", get(suggest))
         .route("/quotes", post(create_quote))
 }
 
-#[derive(Debug, Deserialize)]
+/// claves publicas vigentes para validar jwt de administradores fuera del api,
+/// se monta en /.well-known/jwks.json fuera del prefijo /api
+pub async fn jwks(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(state.jwt_keys.jwks().clone()),
+    )
+}
+
+// valores por filtro multiple, evita consultas con listas enormes
+const MAX_FILTER_VALUES: usize = 50;
+
+// filtros multiples con la clave repetida: ?brand=Mindray&brand=Hettich
+#[derive(Debug, Default, Deserialize)]
 pub struct ProductQuery {
-    pub category: Option<String>,
+    #[serde(default)]
+    pub category: Vec<String>,
     pub search: Option<String>,
+    #[serde(default)]
+    pub brand: Vec<String>,
+    #[serde(default)]
+    pub origin_country: Vec<String>,
+    #[serde(default)]
+    pub warranty: Vec<i32>,
+    // condiciones clave:operador:valor sobre especificaciones definidas
+    #[serde(default)]
+    pub spec: Vec<String>,
+    // newest, relevance, name, brand, warranty o spec.clave, con - para invertir
+    pub sort: Option<String>,
     pub page: Option<i32>,
     pub limit: Option<i32>,
+    // next_cursor de la respuesta anterior, en lugar de page
+    pub cursor: Option<String>,
 }
 
 async fn get_products(
     State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
     Query(params): Query<ProductQuery>,
-) -> ApiResult<Json<ProductListResponse>> {
-    let page = params.page.unwrap_or(1).max(1);
-    let limit = params.limit.unwrap_or(20).min(100);
-    let offset = (page - 1) * limit;
-    
-    let mut query = String::from(
-        "SELECT * FROM products WHERE is_active = true"
-    );
-    
-    // filtro por categoria
-    if let Some(category_slug) = &params.category {
-        let clean = sanitize_text(category_slug);
-        query.push_str(&format!(
-            " AND category_id = (SELECT id FROM categories WHERE slug = '{}')",
-            clean
-        ));
+) -> ApiResult<(HeaderMap, Json<ProductListResponse>)> {
+    for (name, count) in [
+        ("category", params.category.len()),
+        ("brand", params.brand.len()),
+        ("origin_country", params.origin_country.len()),
+        ("warranty", params.warranty.len()),
+    ] {
+        if count > MAX_FILTER_VALUES {
+            return Err(ApiError::Validation(format!(
+                "El filtro {} admite hasta {} valores", name, MAX_FILTER_VALUES
+            )));
+        }
     }
-    
-    // filtro por busqueda
-    if let Some(search) = &params.search {
-        let clean = sanitize_text(search);
-        query.push_str(&format!(
-            " AND (name ILIKE '%{}%' OR description ILIKE '%{}%')",
-            clean, clean
-        ));
+    if params.spec.len() > MAX_SPEC_FILTERS {
+        return Err(ApiError::Validation(format!(
+            "Se admiten hasta {} filtros de especificacion", MAX_SPEC_FILTERS
+        )));
     }
+
+    // las claves de especificacion se resuelven contra las definiciones de las
+    // categorias elegidas, o de todas si no se filtra por categoria
+    let categories = filter_values(&params.category);
+    let sort = params.sort.as_deref().map(str::trim).filter(|sort| !sort.is_empty());
+    let definitions = if params.spec.is_empty() && sort.is_none() && categories.is_empty() {
+        Vec::new()
+    } else {
+        specs::for_category_slugs(&state.db, &categories).await?
+    };
+    let spec_filters = params
+        .spec
+        .iter()
+        .map(|raw| specs::resolve_filter(&definitions, &SpecCondition::parse(raw)?))
+        .collect::<ApiResult<Vec<SpecFilter>>>()?;
+    let sort = ProductSort::parse(sort, search_term(&params).is_some(), &definitions)?;
+    let cursor = sort.cursor(params.cursor.as_deref(), params.page)?;
+    let ranges = if categories.is_empty() {
+        Vec::new()
+    } else {
+        specs::range_definitions(&definitions)
+    };
+
+    let pagination = Pagination::new(params.page, params.limit, 20, 100);
+    let listing = product_listing(&params, &spec_filters);
+    let columns = if search_term(&params).is_some() { SEARCH_COLUMNS } else { "products.*" };
+    let page_listing = match sort.join() {
+        Some(join) => listing.clone().join(join),
+        None => listing.clone(),
+    }
+    .sort_by(sort.keys());
+    let page_listing = match &cursor {
+        Some(cursor) => page_listing.after(cursor, pagination.limit)?,
+        None => page_listing.paginate(pagination),
+    };
     
-    query.push_str(" ORDER BY created_at DESC");
-    query.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
-    
-    let products: Vec<Product> = sqlx::query_as(&query)
-        .fetch_all(&state.db)
-        .await?;
+    let mut page_query = page_listing.select(columns);
+    let mut count_query = listing.count();
     
-    // obtener total de resultados
-    let count_query = "SELECT COUNT(*) as count FROM products WHERE is_active = true";
-    let total: (i64,) = sqlx::query_as(count_query)
-        .fetch_one(&state.db)
-        .await?;
+    // pagina, total con los mismos filtros y facetas en paralelo
+    let (products, (total,), facets): (Vec<ProductListItem>, (i64,), _) = tokio::try_join!(
+        page_query.build_query_as().fetch_all(&state.db),
+        count_query.build_query_as().fetch_one(&state.db),
+        product_facets(&state, &params, &spec_filters, &ranges),
+    )?;
+    let (products, meta, next_cursor) = sort.page(products, pagination, cursor.is_some(), total);
+
+    let did_you_mean = match search_term(&params) {
+        Some(term) if total == 0 => search::did_you_mean(&state.db, term).await?,
+        _ => None,
+    };
     
-    Ok(Json(ProductListResponse {
+    let headers = if cursor.is_some() {
+        cursor_links(&uri, next_cursor.as_deref())
+    } else {
+        page_links(&uri, &meta)
+    };
+    Ok((headers, Json(ProductListResponse {
         products,
-        total: total.0,
-        page,
-        limit,
-    }))
+        meta,
+        next_cursor,
+        did_you_mean,
+        facets: Some(facets),
+    })))
+}
+
+// relevancia y fragmentos resaltados. las etiquetas html que deja ammonia se
+// quitan antes de resaltar para no cortar una etiqueta a la mitad, asi el
+// unico html del fragmento es <mark>
+const SEARCH_COLUMNS: &str = "products.*, \
+    ts_rank(search_vector, search.query) AS rank, \
+    ts_headline('spanish_unaccent', regexp_replace(name, '<[^>]*>', ' ', 'g'), search.query, \
+        'HighlightAll=true, StartSel=<mark>, StopSel=</mark>') AS name_highlight, \
+    ts_headline('spanish_unaccent', regexp_replace(COALESCE(description, ''), '<[^>]*>', ' ', 'g'), search.query, \
+        'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MinWords=8, MaxWords=25, FragmentDelimiter=\" ... \"') \
+        AS description_highlight";
+
+// termino normalizado (minusculas, sin acentos), su consulta de texto completo
+// y la clave de modelo sin espacios ni guiones
+const SEARCH_JOIN: &str = ", (SELECT websearch_to_tsquery('spanish_unaccent', term) AS query, term, \
+    regexp_replace(term, '[^a-z0-9]', '', 'g') AS model \
+    FROM (SELECT lower(f_unaccent(";
+
+// texto completo, similitud por trigramas en nombre, marca y modelo (desde 3
+// letras para no traer todo con "de") o el modelo exacto
+const SEARCH_FILTER: &str = "(search_vector @@ search.query \
+    OR (length(search.term) >= 3 AND search_terms %> search.term) \
+    OR (search.model <> '' AND model_key = search.model))";
+
+fn search_term(params: &ProductQuery) -> Option<&str> {
+    params.search.as_deref().map(str::trim).filter(|term| !term.is_empty())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Facet {
+    Category,
+    Brand,
+    OriginCountry,
+    Warranty,
+}
+
+impl Facet {
+    // valor, etiqueta y conteo por grupo. la categoria se agrupa por id y se
+    // muestra por slug y nombre
+    fn columns(self) -> &'static str {
+        match self {
+            Facet::Category => "(SELECT slug FROM categories WHERE categories.id = category_id) AS value, \
+                (SELECT name FROM categories WHERE categories.id = category_id) AS label, COUNT(*) AS count",
+            Facet::Brand => "brand AS value, NULL::text AS label, COUNT(*) AS count",
+            Facet::OriginCountry => "origin_country AS value, NULL::text AS label, COUNT(*) AS count",
+            Facet::Warranty => "warranty_period::text AS value, NULL::text AS label, COUNT(*) AS count",
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Facet::Category => "category_id",
+            Facet::Brand => "brand",
+            Facet::OriginCountry => "origin_country",
+            Facet::Warranty => "warranty_period",
+        }
+    }
+
+    fn not_null(self) -> &'static str {
+        match self {
+            Facet::Category => "category_id IS NOT NULL",
+            Facet::Brand => "brand IS NOT NULL",
+            Facet::OriginCountry => "origin_country IS NOT NULL",
+            Facet::Warranty => "warranty_period IS NOT NULL",
+        }
+    }
+}
+
+// valores sin espacios sobrantes, sin vacios ni repetidos
+fn filter_values(values: &[String]) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for value in values.iter().map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        if !cleaned.iter().any(|c| c == value) {
+            cleaned.push(value.to_string());
+        }
+    }
+    cleaned
+}
+
+fn product_listing(params: &ProductQuery, specs: &[SpecFilter]) -> ListQuery {
+    catalog_listing(params, specs, None)
+}
+
+// filtros del catalogo publico, los valores del cliente solo viajan enlazados.
+// la busqueda usa la sintaxis de buscador web: "frase exacta", -excluir, or,
+// sin distinguir acentos y tolerando errores de tipeo. `except` omite el filtro
+// de una faceta para contar sus valores con el resto de filtros
+fn catalog_listing(params: &ProductQuery, specs: &[SpecFilter], except: Option<Facet>) -> ListQuery {
+    let active = |facet: Facet| except != Some(facet);
+    let categories = filter_values(&params.category);
+    let brands = filter_values(&params.brand);
+    let countries = filter_values(&params.origin_country);
+    let mut warranties = params.warranty.clone();
+    warranties.sort_unstable();
+    warranties.dedup();
+
+    let mut listing = ListQuery::new("products")
+        .filter(visibility::public_product())
+        .filter_opt((active(Facet::Category) && !categories.is_empty()).then(|| {
+            Filter::sql("category_id IN (SELECT id FROM categories WHERE slug = ANY(")
+                .bind(categories)
+                .and_sql("))")
+        }))
+        .filter_opt((active(Facet::Brand) && !brands.is_empty()).then(|| Filter::any("brand", brands)))
+        .filter_opt(
+            (active(Facet::OriginCountry) && !countries.is_empty())
+                .then(|| Filter::any("origin_country", countries)),
+        )
+        .filter_opt(
+            (active(Facet::Warranty) && !warranties.is_empty())
+                .then(|| Filter::any("warranty_period", warranties)),
+        );
+    for spec in specs {
+        listing = listing.filter(spec.filter.clone());
+    }
+
+    match search_term(params) {
+        Some(term) => listing
+            .join(Filter::sql(SEARCH_JOIN).bind(term).and_sql(")) AS term) AS input) AS search"))
+            .filter(Filter::sql(SEARCH_FILTER)),
+        None => listing,
+    }
+}
+
+// hasta 100 valores por faceta, los de mas productos primero
+async fn facet_counts(
+    state: &AppState,
+    params: &ProductQuery,
+    specs: &[SpecFilter],
+    facet: Facet,
+) -> Result<Vec<FacetCount>, sqlx::Error> {
+    let mut query = facet_query(params, specs, facet);
+    query.build_query_as().fetch_all(&state.db).await
+}
+
+fn facet_query(params: &ProductQuery, specs: &[SpecFilter], facet: Facet) -> sqlx::QueryBuilder<'static, sqlx::Postgres> {
+    catalog_listing(params, specs, Some(facet))
+        .filter(Filter::sql(facet.not_null()))
+        .group_by(facet.column())
+        .order_by("count DESC, value")
+        .paginate(Pagination { page: 1, limit: 100 })
+        .select(facet.columns())
+}
+
+// minimo y maximo de una especificacion con los demas filtros, sin las
+// condiciones sobre la misma clave
+fn spec_range_query(params: &ProductQuery, specs: &[SpecFilter], key: &str) -> sqlx::QueryBuilder<'static, sqlx::Postgres> {
+    let others: Vec<SpecFilter> = specs.iter().filter(|spec| spec.key != key).cloned().collect();
+    catalog_listing(params, &others, None)
+        .join(specs::range_join(key))
+        .select("MIN(spec_value.value) AS min, MAX(spec_value.value) AS max, COUNT(spec_value.value) AS count")
+}
+
+async fn spec_ranges(
+    state: &AppState,
+    params: &ProductQuery,
+    specs: &[SpecFilter],
+    ranges: &[&SpecDefinition],
+) -> Result<Vec<SpecRangeFacet>, sqlx::Error> {
+    let mut facets = Vec::with_capacity(ranges.len());
+    for definition in ranges {
+        let (min, max, count): (Option<f64>, Option<f64>, i64) = spec_range_query(params, specs, &definition.key)
+            .build_query_as()
+            .fetch_one(&state.db)
+            .await?;
+        facets.push(SpecRangeFacet {
+            key: definition.key.clone(),
+            label: definition.label.clone(),
+            unit: definition.unit.clone(),
+            min,
+            max,
+            count,
+        });
+    }
+    Ok(facets)
+}
+
+async fn product_facets(
+    state: &AppState,
+    params: &ProductQuery,
+    specs: &[SpecFilter],
+    ranges: &[&SpecDefinition],
+) -> Result<ProductFacets, sqlx::Error> {
+    let (category, brand, origin_country, warranty, specs) = tokio::try_join!(
+        facet_counts(state, params, specs, Facet::Category),
+        facet_counts(state, params, specs, Facet::Brand),
+        facet_counts(state, params, specs, Facet::OriginCountry),
+        facet_counts(state, params, specs, Facet::Warranty),
+        spec_ranges(state, params, specs, ranges),
+    )?;
+    Ok(ProductFacets { category, brand, origin_country, warranty, specs })
 }
 
 async fn get_product_by_slug(
     State(state): State<AppState>,
     Path(slug): Path<String>,
 ) -> ApiResult<Json<Product>> {
-    let product = sqlx::query_as::<_, Product>(
-        "SELECT * FROM products WHERE slug = $1 AND is_active = true"
-    )
+    let product = sqlx::query_as::<_, Product>(&format!(
+        "SELECT * FROM products WHERE slug = $1 AND {}",
+        visibility::PUBLIC_PRODUCT
+    ))
     .bind(&slug)
     .fetch_optional(&state.db)
     .await?
@@ -95,12 +366,83 @@ async fn get_product_by_slug(
     Ok(Json(product))
 }
 
+// comparacion lado a lado de productos activos, las columnas siguen el orden
+// de `slugs`
+async fn compare_products(
+    State(state): State<AppState>,
+    Query(params): Query<CompareQuery>,
+) -> ApiResult<Json<ComparisonResponse>> {
+    let slugs = compare::parse_slugs(params.slugs.as_deref())?;
+
+    let found = sqlx::query_as::<_, Product>(&format!(
+        "SELECT * FROM products WHERE slug = ANY($1) AND {}",
+        visibility::PUBLIC_PRODUCT
+    ))
+    .bind(&slugs)
+    .fetch_all(&state.db)
+    .await?;
+
+    let missing: Vec<String> = slugs
+        .iter()
+        .filter(|slug| !found.iter().any(|p| &p.slug == *slug))
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        return Err(ApiError::MissingResources(missing));
+    }
+
+    let mut products = found;
+    products.sort_by_key(|p| slugs.iter().position(|slug| slug == &p.slug));
+
+    let mut category_ids: Vec<i32> = products.iter().filter_map(|p| p.category_id).collect();
+    category_ids.sort_unstable();
+    category_ids.dedup();
+    let definitions = specs::for_categories(&state.db, &category_ids).await?;
+
+    Ok(Json(compare::build(&products, &definitions)))
+}
+
+// autocompletado del buscador, responde desde el indice en memoria
+async fn suggest(
+    State(state): State<AppState>,
+    Query(params): Query<SuggestQuery>,
+) -> Json<SuggestResponse> {
+    let limit = params.limit.unwrap_or(8).clamp(1, 20);
+    let query = params.q.as_deref().unwrap_or_default();
+    // una consulta mas larga que cualquier nombre no puede coincidir
+    let suggestions = if query.chars().count() > 100 {
+        Vec::new()
+    } else {
+        state.suggest.suggest(query, limit)
+    };
+
+    Json(SuggestResponse { suggestions })
+}
+
+// especificaciones definidas para la categoria, para armar los filtros
+async fn get_category_specs(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> ApiResult<Json<Vec<SpecDefinition>>> {
+    let (category_id,): (i32,) = sqlx::query_as(&format!(
+        "SELECT id FROM categories WHERE slug = $1 AND {}",
+        visibility::PUBLIC_CATEGORY
+    ))
+    .bind(&slug)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("Categoria no encontrada".to_string()))?;
+
+    Ok(Json(specs::for_category(&state.db, category_id).await?))
+}
+
 async fn get_categories(
     State(state): State<AppState>,
 ) -> ApiResult<Json<Vec<Category>>> {
-    let categories = sqlx::query_as::<_, Category>(
-        "SELECT * FROM categories ORDER BY name ASC"
-    )
+    let categories = sqlx::query_as::<_, Category>(&format!(
+        "SELECT * FROM categories WHERE {} ORDER BY name ASC",
+        visibility::PUBLIC_CATEGORY
+    ))
     .fetch_all(&state.db)
     .await?;
     
@@ -149,9 +491,10 @@ async fn create_quote(
     .await?;
     
     // obtener nombres de productos para email
-    let product_names: Vec<String> = sqlx::query_as::<_, (String,)>(
-        "SELECT name FROM products WHERE id = ANY($1)"
-    )
+    let product_names: Vec<String> = sqlx::query_as::<_, (String,)>(&format!(
+        "SELECT name FROM products WHERE id = ANY($1) AND {}",
+        visibility::PUBLIC_PRODUCT
+    ))
     .bind(&payload.product_ids)
     .fetch_all(&state.db)
     .await?
@@ -176,4 +519,234 @@ async fn create_quote(
         "code": "OK",
         "message": "Solicitud de cotizacion enviada exitosamente"
     })))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fixtures::{insert_product, require_utf8};
+    use crate::services::listing::fixtures::assert_binds_hostile;
+    use crate::services::visibility::PUBLIC_PRODUCT;
+    use sqlx::PgPool;
+
+    // la pagina de una busqueda tal como la arma get_products
+    async fn search(db: &PgPool, term: &str) -> Vec<ProductListItem> {
+        let params = ProductQuery { search: Some(term.to_string()), ..Default::default() };
+        product_listing(&params, &[])
+            .sort_by(ProductSort::Relevance.keys())
+            .paginate(Pagination::new(None, None, 20, 100))
+            .select(SEARCH_COLUMNS)
+            .build_query_as()
+            .fetch_all(db)
+            .await
+            .unwrap()
+    }
+
+    fn ids(products: &[ProductListItem]) -> Vec<i32> {
+        products.iter().map(|item| item.product.id).collect()
+    }
+
+    #[test]
+    fn test_catalogo_enlaza_filtros_hostiles() {
+        assert_binds_hostile(
+            |input| {
+                let params = ProductQuery {
+                    category: vec![input.to_string()],
+                    search: Some(input.to_string()),
+                    brand: vec![input.to_string(), "Mindray".to_string()],
+                    origin_country: vec![input.to_string()],
+                    warranty: vec![24, 12, 24],
+                    page: Some(1),
+                    limit: Some(20),
+                    ..Default::default()
+                };
+                product_listing(&params, &[]).select("products.*").into_sql()
+            },
+            &format!(
+                "SELECT products.* FROM products{}$1)) AS term) AS input) AS search \
+                 WHERE {} \
+                 AND category_id IN (SELECT id FROM categories WHERE slug = ANY($2)) \
+                 AND brand = ANY($3) AND origin_country = ANY($4) AND warranty_period = ANY($5) \
+                 AND {}",
+                SEARCH_JOIN, PUBLIC_PRODUCT, SEARCH_FILTER
+            ),
+        );
+    }
+
+    #[test]
+    fn test_sql_de_la_busqueda_enlaza_el_termino() {
+        let params = ProductQuery {
+            search: Some("monitor mindray".to_string()),
+            ..Default::default()
+        };
+        let sql = product_listing(&params, &[])
+            .sort_by(ProductSort::Relevance.keys())
+            .paginate(Pagination::new(None, None, 20, 100))
+            .select(SEARCH_COLUMNS)
+            .into_sql();
+
+        assert!(sql.contains("ts_rank(search_vector, search.query) AS rank"), "{}", sql);
+        assert!(sql.contains(
+            "ts_headline('spanish_unaccent', regexp_replace(name, '<[^>]*>', ' ', 'g'), search.query, \
+             'HighlightAll=true, StartSel=<mark>, StopSel=</mark>') AS name_highlight"
+        ), "{}", sql);
+        assert!(sql.contains("search.query, 'StartSel=<mark>, StopSel=</mark>, MaxFragments=2"), "{}", sql);
+        assert!(sql.contains("AS description_highlight"), "{}", sql);
+        // el termino solo viaja enlazado
+        assert!(sql.contains("FROM (SELECT lower(f_unaccent($1)) AS term) AS input) AS search"), "{}", sql);
+        assert!(!sql.contains("mindray"), "{}", sql);
+        assert!(sql.contains(
+            "ORDER BY (search.model <> '' AND model_key = search.model) DESC, ts_rank(search_vector, search.query)::float8 DESC"
+        ), "{}", sql);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requiere postgres, ver services::fixtures"]
+    async fn test_el_nombre_pesa_mas_que_la_descripcion(db: PgPool) {
+        // el del nombre se crea antes, solo la relevancia lo puede poner primero
+        let in_name = insert_product(&db, "Desfibrilador bifasico", None, "Equipo para emergencias").await;
+        let in_description = insert_product(
+            &db,
+            "Carro de paro",
+            None,
+            "<p>Carro con cajones, soporte para desfibrilador y tabla de reanimacion</p>",
+        )
+        .await;
+
+        let found = search(&db, "desfibrilador").await;
+        assert_eq!(ids(&found), vec![in_name, in_description]);
+        assert!(found[0].rank > found[1].rank);
+
+        assert_eq!(found[0].name_highlight.as_deref(), Some("<mark>Desfibrilador</mark> bifasico"));
+        assert_eq!(found[1].name_highlight.as_deref(), Some("Carro de paro"));
+        // el html de la descripcion se quita, solo queda <mark>
+        let snippet = found[1].description_highlight.as_deref().unwrap();
+        assert!(snippet.contains("soporte para <mark>desfibrilador</mark> y tabla"), "{}", snippet);
+        assert!(!snippet.contains("<p>"), "{}", snippet);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requiere postgres, ver services::fixtures"]
+    async fn test_modelo_vacio_no_cambia_el_orden(db: PgPool) {
+        // "Ωμέγα" no deja letras latinas ni digitos, su clave de modelo queda
+        // vacia igual que la de un producto sin modelo
+        require_utf8(&db).await;
+        let without_model = insert_product(&db, "Bomba de infusion", None, "Compatible con el sistema ωμέγα").await;
+        let in_name = insert_product(&db, "Monitor ωμέγα", Some("OM-1"), "Monitor ωμέγα de cabecera").await;
+
+        assert_eq!(ids(&search(&db, "Ωμέγα").await), vec![in_name, without_model]);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requiere postgres, ver services::fixtures"]
+    async fn test_sin_acentos_con_errores_y_por_modelo(db: PgPool) {
+        require_utf8(&db).await;
+        let ecografo = insert_product(&db, "Ecógrafo portátil", Some("EBA-200"), "Ultrasonido Doppler color").await;
+        // mas reciente y nombra el modelo varias veces, pero no es ese modelo
+        let transductor = insert_product(
+            &db,
+            "Transductor convexo",
+            None,
+            "Transductor para EBA 200, compatible con el EBA 200 y el EBA 200 plus",
+        )
+        .await;
+
+        assert_eq!(ids(&search(&db, "ecografo portatil").await), vec![ecografo]);
+        assert_eq!(ids(&search(&db, "ECÓGRAFO").await), vec![ecografo]);
+        // letra repetida: no coincide el texto completo, si los trigramas
+        assert_eq!(ids(&search(&db, "eccografo").await), vec![ecografo]);
+        // "eba 200", "Eba200" y "EBA-200" son la misma clave de modelo
+        assert_eq!(ids(&search(&db, "eba 200").await), vec![ecografo, transductor]);
+        assert_eq!(ids(&search(&db, "Eba200").await), vec![ecografo]);
+    }
+
+    #[test]
+    fn test_busqueda_vacia_no_filtra() {
+        let params = ProductQuery {
+            search: Some("   ".to_string()),
+            brand: vec![" ".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            product_listing(&params, &[]).select("*").into_sql(),
+            format!("SELECT * FROM products WHERE {}", PUBLIC_PRODUCT)
+        );
+    }
+
+    #[test]
+    fn test_faceta_omite_su_propio_filtro() {
+        let params = ProductQuery {
+            brand: vec!["Mindray".to_string()],
+            origin_country: vec!["China".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            facet_query(&params, &[], Facet::Brand).into_sql(),
+            format!(
+                "SELECT brand AS value, NULL::text AS label, COUNT(*) AS count FROM products \
+                 WHERE {} AND origin_country = ANY($1) AND brand IS NOT NULL \
+                 GROUP BY brand ORDER BY count DESC, value LIMIT $2 OFFSET $3",
+                PUBLIC_PRODUCT
+            )
+        );
+        let sql = facet_query(&params, &[], Facet::OriginCountry).into_sql();
+        assert!(sql.contains("brand = ANY($1)"));
+        assert!(!sql.contains("origin_country = ANY"));
+        let sql = facet_query(&params, &[], Facet::Warranty).into_sql();
+        assert!(sql.contains("brand = ANY($1) AND origin_country = ANY($2)"));
+    }
+
+    #[test]
+    fn test_rango_de_especificacion_omite_su_propia_condicion() {
+        use crate::services::specs::SpecFilter;
+
+        let spec = |key: &str, value: f64| SpecFilter {
+            key: key.to_string(),
+            filter: Filter::sql("spec_number(specifications, ").bind(key).and_sql(") >= ").bind(value),
+        };
+        let params = ProductQuery {
+            category: vec!["ventiladores".to_string()],
+            ..Default::default()
+        };
+        let filters = vec![spec("autonomia", 4.0), spec("peso", 2.0)];
+
+        assert_eq!(
+            product_listing(&params, &filters).select("*").into_sql(),
+            format!(
+                "SELECT * FROM products WHERE {} \
+                 AND category_id IN (SELECT id FROM categories WHERE slug = ANY($1)) \
+                 AND spec_number(specifications, $2) >= $3 AND spec_number(specifications, $4) >= $5",
+                PUBLIC_PRODUCT
+            )
+        );
+        assert_eq!(
+            spec_range_query(&params, &filters, "autonomia").into_sql(),
+            format!(
+                "SELECT MIN(spec_value.value) AS min, MAX(spec_value.value) AS max, COUNT(spec_value.value) AS count \
+                 FROM products, LATERAL (SELECT spec_number(specifications, $1) AS value) AS spec_value \
+                 WHERE {} AND category_id IN (SELECT id FROM categories WHERE slug = ANY($2)) \
+                 AND spec_number(specifications, $3) >= $4",
+                PUBLIC_PRODUCT
+            )
+        );
+        // las facetas de valores respetan todas las condiciones
+        assert!(facet_query(&params, &filters, Facet::Brand).into_sql().contains("spec_number(specifications, $4) >= $5"));
+    }
+
+    #[tokio::test]
+    async fn test_filtros_multiples_desde_la_url() {
+        use axum::extract::FromRequestParts;
+
+        let (mut parts, _) = axum::http::Request::builder()
+            .uri("/api/products?brand=Mindray&brand=Dr%C3%A4ger&warranty=12&category=monitores&spec=peso:lte:5&spec=modo:in:a%7Cb")
+            .body(())
+            .unwrap()
+            .into_parts();
+        let Query(params) = Query::<ProductQuery>::from_request_parts(&mut parts, &()).await.unwrap();
+        assert_eq!(params.brand, vec!["Mindray", "Dräger"]);
+        assert_eq!(params.warranty, vec![12]);
+        assert_eq!(params.category, vec!["monitores"]);
+        assert!(params.origin_country.is_empty());
+        assert_eq!(params.spec, vec!["peso:lte:5", "modo:in:a|b"]);
+    }
+}
@@ -0,0 +1,218 @@
+use serde::Serialize;
+use sqlx::PgConnection;
+use std::net::{IpAddr, Ipv4Addr};
+
+use crate::{error::ApiResult, models::Admin, services::auth::Claims};
+
+// acciones auditadas, el texto se guarda en audit_events.action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Login,
+    ProductCreate,
+    ProductUpdate,
+    ProductDelete,
+    ProductStatusUpdate,
+    ProductRestore,
+    ProductUndelete,
+    ProductPurge,
+    CategoryCreate,
+    CategoryUpdate,
+    CategoryDelete,
+    CategoryUndelete,
+    CategoryPurge,
+    SpecDefinitionCreate,
+    SpecDefinitionUpdate,
+    SpecDefinitionDelete,
+    SpecTemplateUpdate,
+    QuoteStatusUpdate,
+    FileUpload,
+    AdminInvite,
+    AdminCreate,
+    AdminStatusUpdate,
+    AdminDelete,
+    AdminBootstrap,
+    PasswordChange,
+    PasswordReset,
+    TotpEnable,
+    TotpDisable,
+    RecoveryCodesRegenerate,
+    SecuritySettingsUpdate,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Login => "auth.login",
+            AuditAction::ProductCreate => "product.create",
+            AuditAction::ProductUpdate => "product.update",
+            AuditAction::ProductDelete => "product.delete",
+            AuditAction::ProductStatusUpdate => "product.status_update",
+            AuditAction::ProductRestore => "product.restore",
+            AuditAction::ProductUndelete => "product.undelete",
+            AuditAction::ProductPurge => "product.purge",
+            AuditAction::CategoryCreate => "category.create",
+            AuditAction::CategoryUpdate => "category.update",
+            AuditAction::CategoryDelete => "category.delete",
+            AuditAction::CategoryUndelete => "category.undelete",
+            AuditAction::CategoryPurge => "category.purge",
+            AuditAction::SpecDefinitionCreate => "category.spec_create",
+            AuditAction::SpecDefinitionUpdate => "category.spec_update",
+            AuditAction::SpecDefinitionDelete => "category.spec_delete",
+            AuditAction::SpecTemplateUpdate => "category.spec_template_update",
+            AuditAction::QuoteStatusUpdate => "quote.status_update",
+            AuditAction::FileUpload => "file.upload",
+            AuditAction::AdminInvite => "admin.invite",
+            AuditAction::AdminCreate => "admin.create",
+            AuditAction::AdminStatusUpdate => "admin.status_update",
+            AuditAction::AdminDelete => "admin.delete",
+            AuditAction::AdminBootstrap => "admin.bootstrap",
+            AuditAction::PasswordChange => "admin.password_change",
+            AuditAction::PasswordReset => "admin.password_reset",
+            AuditAction::TotpEnable => "admin.2fa_enable",
+            AuditAction::TotpDisable => "admin.2fa_disable",
+            AuditAction::RecoveryCodesRegenerate => "admin.2fa_recovery_regenerate",
+            AuditAction::SecuritySettingsUpdate => "settings.security_update",
+        }
+    }
+
+    /// tipo de entidad afectada, es el prefijo de la accion
+    pub fn entity_type(&self) -> &'static str {
+        match self {
+            AuditAction::Login => "admin",
+            other => other.as_str().split('.').next().unwrap_or_default(),
+        }
+    }
+}
+
+/// quien ejecuta la accion, el email se guarda aparte del id para no perderlo
+/// si la cuenta se elimina
+#[derive(Debug, Clone)]
+pub struct Actor {
+    // None en las tareas programadas del servidor
+    pub admin_id: Option<i32>,
+    pub email: String,
+    pub ip: IpAddr,
+}
+
+impl Actor {
+    pub fn new(claims: &Claims, ip: IpAddr) -> Self {
+        Self {
+            admin_id: Some(claims.admin_id),
+            email: claims.sub.clone(),
+            ip,
+        }
+    }
+
+    // login y acciones previas a emitir el jwt
+    pub fn admin(admin: &Admin, ip: IpAddr) -> Self {
+        Self {
+            admin_id: Some(admin.id),
+            email: admin.email.clone(),
+            ip,
+        }
+    }
+
+    // tareas programadas como la purga de la papelera
+    pub fn system() -> Self {
+        Self {
+            admin_id: None,
+            email: "sistema".to_string(),
+            ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AuditEvent {
+    action: AuditAction,
+    entity_id: Option<String>,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+}
+
+impl AuditEvent {
+    pub fn new(action: AuditAction) -> Self {
+        Self {
+            action,
+            entity_id: None,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn entity(mut self, id: impl ToString) -> Self {
+        self.entity_id = Some(id.to_string());
+        self
+    }
+
+    pub fn before<T: Serialize>(mut self, value: &T) -> Self {
+        self.before = serde_json::to_value(value).ok();
+        self
+    }
+
+    pub fn after<T: Serialize>(mut self, value: &T) -> Self {
+        self.after = serde_json::to_value(value).ok();
+        self
+    }
+}
+
+/// se escribe en la misma transaccion que el cambio, si el registro falla
+/// el cambio tampoco se confirma
+pub async fn record(conn: &mut PgConnection, actor: &Actor, event: AuditEvent) -> ApiResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_events (actor_id, actor_email, ip, action, entity_type, entity_id, before, after)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#
+    )
+    .bind(actor.admin_id)
+    .bind(&actor.email)
+    .bind(actor.ip.to_string())
+    .bind(event.action.as_str())
+    .bind(event.action.entity_type())
+    .bind(&event.entity_id)
+    .bind(&event.before)
+    .bind(&event.after)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tipo_de_entidad_por_accion() {
+        assert_eq!(AuditAction::ProductStatusUpdate.entity_type(), "product");
+        assert_eq!(AuditAction::QuoteStatusUpdate.entity_type(), "quote");
+        assert_eq!(AuditAction::FileUpload.entity_type(), "file");
+        assert_eq!(AuditAction::Login.entity_type(), "admin");
+        assert_eq!(AuditAction::PasswordChange.entity_type(), "admin");
+        assert_eq!(AuditAction::SecuritySettingsUpdate.entity_type(), "settings");
+    }
+
+    #[test]
+    fn test_evento_omite_secretos_del_admin() {
+        let admin = Admin {
+            id: 1,
+            email: "owner@labmedical.com".to_string(),
+            password_hash: "$argon2id$secreto".to_string(),
+            name: None,
+            role: crate::models::AdminRole::Owner,
+            is_active: true,
+            token_version: 0,
+            totp_secret: Some("JBSWY3DPEHPK3PXP".to_string()),
+            totp_enabled: true,
+            totp_last_step: None,
+            created_at: chrono::Utc::now(),
+            last_login: None,
+        };
+        let event = AuditEvent::new(AuditAction::AdminStatusUpdate).entity(admin.id).after(&admin);
+        let after = event.after.unwrap().to_string();
+        assert!(!after.contains("argon2id"));
+        assert!(!after.contains("JBSWY3DPEHPK3PXP"));
+        assert_eq!(event.entity_id.as_deref(), Some("1"));
+    }
+}
@@ -1,34 +1,67 @@
 #![allow(dead_code)]
 use argon2::{
+    Algorithm,
     Argon2,
+    Params,
     PasswordHash,
     PasswordHasher,
     PasswordVerifier,
-    password_hash::{SaltString, rand_core::OsRng},
+    Version,
+    password_hash::{SaltString, rand_core::{OsRng, RngCore}},
 };
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use data_encoding::{BASE64URL_NOPAD, HEXLOWER};
+use jsonwebtoken::{decode, decode_header, encode, Validation};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
 use chrono::{Duration, Utc};
-use crate::error::{ApiError, ApiResult};
+use crate::{
+    config::PasswordHashing,
+    error::{ApiError, ApiResult},
+    models::{Admin, AdminRole, Permission},
+    services::jwt_keys::JwtKeys,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // Sujeto (email del admin)
+    pub admin_id: i32,
+    pub role: AdminRole,
+    pub ver: i32,    // generacion de tokens del admin al emitirse
     pub exp: i64,    // Tiempo de expiración
     pub iat: i64,    // Emitido en
 }
 
+impl Claims {
+    /// rechaza con 403 si el rol del token no habilita el permiso pedido
+    pub fn require(&self, permission: Permission) -> ApiResult<()> {
+        if self.role.allows(permission) {
+            return Ok(());
+        }
+        Err(ApiError::Forbidden(format!(
+            "{} ({}) sin permiso {:?}",
+            self.sub, self.role, permission
+        )))
+    }
+}
+
+fn argon2_for(hashing: &PasswordHashing) -> ApiResult<Argon2<'static>> {
+    let params = Params::new(hashing.memory_kib, hashing.iterations, hashing.parallelism, None)
+        .map_err(|e| ApiError::Internal(format!("Parametros argon2 invalidos: {}", e)))?;
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
 /// hashea contrasena usando argon2id (resistente a gpu y side-channel attacks)
-pub fn hash_password(password: &str) -> ApiResult<String> {
+pub fn hash_password(password: &str, hashing: &PasswordHashing) -> ApiResult<String> {
     let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
+    let argon2 = argon2_for(hashing)?;
     
     argon2.hash_password(password.as_bytes(), &salt)
         .map(|hash| hash.to_string())
         .map_err(|e| ApiError::Internal(format!("Error al hashear contrasena: {}", e)))
 }
 
-/// verifica contrasena contra hash argon2id
+/// verifica contrasena contra hash argon2id, los parametros se leen del propio hash
 pub fn verify_password(password: &str, hash: &str) -> ApiResult<bool> {
     let parsed_hash = PasswordHash::new(hash)
         .map_err(|e| ApiError::Internal(format!("Hash invalido: {}", e)))?;
@@ -38,35 +71,242 @@ pub fn verify_password(password: &str, hash: &str) -> ApiResult<bool> {
         .is_ok())
 }
 
-/// genera jwt con expiracion de 2 horas
-pub fn generate_jwt(email: &str, secret: &str) -> ApiResult<String> {
+/// indica si el hash guardado es mas debil que la configuracion actual,
+/// solo se sube el costo, nunca se rehashea hacia parametros menores
+pub fn needs_rehash(hash: &str, hashing: &PasswordHashing) -> bool {
+    let Ok(parsed_hash) = PasswordHash::new(hash) else {
+        return true;
+    };
+    if parsed_hash.algorithm != Algorithm::Argon2id.ident()
+        || parsed_hash.version != Some(Version::V0x13.into())
+    {
+        return true;
+    }
+    let Ok(params) = Params::try_from(&parsed_hash) else {
+        return true;
+    };
+
+    params.m_cost() < hashing.memory_kib
+        || params.t_cost() < hashing.iterations
+        || params.p_cost() < hashing.parallelism
+}
+
+/// genera un token opaco de 256 bits para enlaces enviados por email
+pub fn generate_secure_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    BASE64URL_NOPAD.encode(&bytes)
+}
+
+/// hash sha-256 de un token opaco, es lo unico que se guarda en base de datos
+pub fn hash_token(token: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(token.as_bytes()))
+}
+
+/// genera jwt de acceso de corta duracion, la sesion se extiende con refresh tokens
+pub fn generate_jwt(admin: &Admin, ttl_minutes: i64, keys: &JwtKeys) -> ApiResult<String> {
     let now = Utc::now();
-    let expiration = now + Duration::hours(2);
+    let expiration = now + Duration::minutes(ttl_minutes);
     
     let claims = Claims {
-        sub: email.to_string(),
+        sub: admin.email.clone(),
+        admin_id: admin.id,
+        role: admin.role,
+        ver: admin.token_version,
         exp: expiration.timestamp(),
         iat: now.timestamp(),
     };
     
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )
-    .map_err(|e| ApiError::Internal(format!("Error al generar jwt: {}", e)))
+    let (header, key) = keys.signing_key();
+    encode(&header, &claims, key)
+        .map_err(|e| ApiError::Internal(format!("Error al generar jwt: {}", e)))
 }
 
-/// verifica y decodifica jwt
-pub fn verify_jwt(token: &str, secret: &str) -> ApiResult<Claims> {
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::default(),
-    )
-    .map(|data| data.claims)
-    .map_err(|e| {
-        tracing::warn!(error_type = "jwt", details = %e, "Token jwt invalido");
+/// verifica firma y expiracion del jwt sin consultar la base de datos,
+/// la clave se elige por el kid de la cabecera
+pub fn decode_jwt(token: &str, keys: &JwtKeys) -> ApiResult<Claims> {
+    let invalid = |details: String| {
+        tracing::warn!(error_type = "jwt", details = %details, "Token jwt invalido");
         ApiError::Auth("Token invalido o expirado".to_string())
-    })
-}
\ No newline at end of file
+    };
+
+    let header = decode_header(token).map_err(|e| invalid(e.to_string()))?;
+    let (algorithm, key) = keys
+        .verification_key(&header)
+        .ok_or_else(|| invalid(format!("clave desconocida: {:?} {:?}", header.alg, header.kid)))?;
+
+    decode::<Claims>(token, key, &Validation::new(algorithm))
+        .map(|data| data.claims)
+        .map_err(|e| invalid(e.to_string()))
+}
+
+#[derive(Debug)]
+pub struct VerifiedClaims {
+    pub claims: Claims,
+    // 2fa es obligatorio y el admin aun no lo activo
+    pub mfa_enrollment_required: bool,
+}
+
+/// verifica el jwt y que siga vigente para el admin, un cambio de contrasena,
+/// desactivacion o cierre de todas las sesiones incrementa token_version
+pub async fn verify_jwt(db: &PgPool, token: &str, keys: &JwtKeys) -> ApiResult<VerifiedClaims> {
+    let claims = decode_jwt(token, keys)?;
+
+    let current: Option<(i32, bool, bool)> = sqlx::query_as(
+        r#"
+        SELECT a.token_version, a.is_active, (s.require_2fa AND NOT a.totp_enabled)
+        FROM admins a CROSS JOIN security_settings s
+        WHERE a.id = $1
+        "#
+    )
+    .bind(claims.admin_id)
+    .fetch_optional(db)
+    .await?;
+
+    match current {
+        Some((version, true, mfa_enrollment_required)) if version == claims.ver => Ok(VerifiedClaims {
+            claims,
+            mfa_enrollment_required,
+        }),
+        _ => {
+            tracing::warn!(error_type = "jwt", admin = %claims.sub, "Token jwt revocado");
+            Err(ApiError::Auth("Token revocado".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::jwt_keys::tests::generate_pem;
+
+    #[test]
+    fn test_token_seguro_unico_y_url_safe() {
+        let a = generate_secure_token();
+        let b = generate_secure_token();
+        assert_ne!(a, b);
+        assert_eq!(a.len(), 43);
+        assert!(a.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn test_hash_token_determinista() {
+        let token = generate_secure_token();
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_eq!(hash_token(&token).len(), 64);
+        assert_ne!(hash_token(&token), hash_token(&generate_secure_token()));
+    }
+
+    fn test_admin() -> Admin {
+        Admin {
+            id: 7,
+            email: "editor@labmedical.com".to_string(),
+            password_hash: String::new(),
+            name: None,
+            role: AdminRole::CatalogEditor,
+            is_active: true,
+            token_version: 3,
+            totp_secret: None,
+            totp_enabled: false,
+            totp_last_step: None,
+            created_at: Utc::now(),
+            last_login: None,
+        }
+    }
+
+    #[test]
+    fn test_jwt_eddsa_con_rotacion_de_claves() {
+        let old_pem = generate_pem();
+        let new_pem = generate_pem();
+        let old_keys = JwtKeys::new(Some(&old_pem), &[], None).unwrap();
+        let old_token = generate_jwt(&test_admin(), 15, &old_keys).unwrap();
+
+        // durante la rotacion la clave anterior sigue verificando
+        let rotating = JwtKeys::new(Some(&new_pem), &[old_pem], None).unwrap();
+        let claims = decode_jwt(&old_token, &rotating).unwrap();
+        assert_eq!(claims.admin_id, 7);
+        assert_eq!(claims.role, AdminRole::CatalogEditor);
+        assert_eq!(claims.ver, 3);
+
+        let new_token = generate_jwt(&test_admin(), 15, &rotating).unwrap();
+        assert!(decode_jwt(&new_token, &rotating).is_ok());
+        assert!(decode_jwt(&new_token, &old_keys).is_err());
+
+        // retirada la clave anterior sus tokens dejan de valer
+        let rotated = JwtKeys::new(Some(&new_pem), &[], None).unwrap();
+        assert!(decode_jwt(&old_token, &rotated).is_err());
+        assert!(decode_jwt(&new_token, &rotated).is_ok());
+    }
+
+    #[test]
+    fn test_jwt_hs256_heredado_solo_con_secreto() {
+        let legacy = JwtKeys::new(None, &[], Some("secreto-anterior")).unwrap();
+        let token = generate_jwt(&test_admin(), 15, &legacy).unwrap();
+        assert!(decode_jwt(&token, &legacy).is_ok());
+
+        // tras migrar a ed25519 los tokens hs256 valen mientras siga JWT_SECRET
+        let migrating = JwtKeys::new(Some(&generate_pem()), &[], Some("secreto-anterior")).unwrap();
+        assert!(decode_jwt(&token, &migrating).is_ok());
+        let migrated = JwtKeys::new(Some(&generate_pem()), &[], None).unwrap();
+        assert!(decode_jwt(&token, &migrated).is_err());
+    }
+
+    #[test]
+    fn test_jwt_rechaza_confusion_de_algoritmo() {
+        let keys = JwtKeys::new(Some(&generate_pem()), &[], Some("secreto")).unwrap();
+        let kid = keys.signing_key().0.kid.unwrap();
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: "x@labmedical.com".to_string(),
+            admin_id: 1,
+            role: AdminRole::Owner,
+            ver: 0,
+            exp: now + 600,
+            iat: now,
+        };
+
+        // hs256 firmado con la clave publica del jwks y el kid de la clave ed25519
+        let x = &keys.jwks().keys[0];
+        let public = match &x.algorithm {
+            jsonwebtoken::jwk::AlgorithmParameters::OctetKeyPair(params) => params.x.clone(),
+            _ => unreachable!(),
+        };
+        let mut header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256);
+        header.kid = Some(kid);
+        let forged = encode(&header, &claims, &jsonwebtoken::EncodingKey::from_secret(public.as_bytes())).unwrap();
+        assert!(decode_jwt(&forged, &keys).is_err());
+    }
+
+    // hash de la migracion inicial para admin123
+    const SEED_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$c2VjdXJlc2FsdGxhYm1lZA$QsM+5bLhEfQkuWfJOBGkVoUdqz3bGJhRkGF2vNNCaQo";
+
+    fn light(memory_kib: u32, iterations: u32) -> PasswordHashing {
+        PasswordHashing { memory_kib, iterations, parallelism: 1 }
+    }
+
+    #[test]
+    fn test_hash_usa_parametros_configurados() {
+        let hash = hash_password("LabMedical2024", &light(64, 3)).unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$m=64,t=3,p=1$"));
+        assert!(verify_password("LabMedical2024", &hash).unwrap());
+        assert!(!verify_password("otra", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_rehash_solo_si_el_hash_es_mas_debil() {
+        assert!(!needs_rehash(SEED_HASH, &PasswordHashing::default()));
+        assert!(needs_rehash(SEED_HASH, &PasswordHashing { memory_kib: 65536, ..PasswordHashing::default() }));
+        assert!(needs_rehash(SEED_HASH, &PasswordHashing { iterations: 3, ..PasswordHashing::default() }));
+        assert!(needs_rehash(SEED_HASH, &PasswordHashing { parallelism: 2, ..PasswordHashing::default() }));
+
+        // bajar la configuracion no degrada hashes existentes
+        assert!(!needs_rehash(SEED_HASH, &light(64, 1)));
+
+        let upgraded = hash_password("admin123", &light(128, 2)).unwrap();
+        assert!(!needs_rehash(&upgraded, &light(128, 2)));
+        assert!(needs_rehash(&upgraded, &light(256, 2)));
+
+        assert!(needs_rehash("$argon2i$v=19$m=19456,t=2,p=1$c2VjdXJlc2FsdGxhYm1lZA$QsM+5bLhEfQkuWfJOBGkVoUdqz3bGJhRkGF2vNNCaQo", &PasswordHashing::default()));
+        assert!(needs_rehash("no-es-un-hash", &PasswordHashing::default()));
+    }
+}
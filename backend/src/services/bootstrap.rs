@@ -0,0 +1,146 @@
+use sqlx::PgPool;
+use validator::ValidateEmail;
+
+use crate::{
+    config::{Config, PasswordPolicy},
+    error::{ApiError, ApiResult},
+    services::{
+        audit::{self, Actor, AuditAction, AuditEvent},
+        auth::{hash_password, verify_password},
+        session,
+        validation::validate_password_policy,
+    },
+};
+
+// cuenta que sembraba la migracion inicial, su contrasena esta publicada en
+// la documentacion anterior
+const SEED_EMAIL: &str = "admin@labmedical.com";
+const SEED_PASSWORD: &str = "admin123";
+
+/// corre al arrancar, despues de las migraciones. desactiva la cuenta sembrada
+/// si conserva la contrasena publica y, si no queda ningun owner activo, crea
+/// uno con BOOTSTRAP_OWNER_EMAIL y BOOTSTRAP_OWNER_PASSWORD
+pub async fn run(db: &PgPool, config: &Config) -> ApiResult<()> {
+    disable_seed_admin(db).await?;
+    ensure_owner(db, config).await
+}
+
+async fn disable_seed_admin(db: &PgPool) -> ApiResult<()> {
+    let seed: Option<(i32, String)> = sqlx::query_as(
+        "SELECT id, password_hash FROM admins WHERE lower(email) = $1 AND is_active = true"
+    )
+    .bind(SEED_EMAIL)
+    .fetch_optional(db)
+    .await?;
+
+    let Some((admin_id, password_hash)) = seed else {
+        return Ok(());
+    };
+    if !verify_password(SEED_PASSWORD, &password_hash).unwrap_or(false) {
+        return Ok(());
+    }
+
+    let mut tx = db.begin().await?;
+    sqlx::query("UPDATE admins SET is_active = false WHERE id = $1")
+        .bind(admin_id)
+        .execute(&mut *tx)
+        .await?;
+    session::revoke_all_sessions(&mut tx, admin_id).await?;
+    audit::record(
+        &mut tx,
+        &Actor::system(),
+        AuditEvent::new(AuditAction::AdminStatusUpdate)
+            .entity(admin_id)
+            .after(&serde_json::json!({ "email": SEED_EMAIL, "is_active": false })),
+    ).await?;
+    tx.commit().await?;
+
+    tracing::warn!(email = SEED_EMAIL, "Cuenta por defecto desactivada, conservaba la contrasena publica");
+    Ok(())
+}
+
+async fn ensure_owner(db: &PgPool, config: &Config) -> ApiResult<()> {
+    let (has_owner,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS(SELECT 1 FROM admins WHERE role = 'owner' AND is_active = true)"
+    )
+    .fetch_one(db)
+    .await?;
+    if has_owner {
+        return Ok(());
+    }
+
+    let (Some(email), Some(password)) = (&config.bootstrap_owner_email, &config.bootstrap_owner_password) else {
+        tracing::warn!("No hay ningun owner activo, defina BOOTSTRAP_OWNER_EMAIL y BOOTSTRAP_OWNER_PASSWORD y reinicie");
+        return Ok(());
+    };
+    let email = check_credentials(email, password, &config.password_policy)?;
+    let password_hash = hash_password(password, &config.password_hashing)?;
+
+    // si la cuenta existe (por ejemplo la sembrada, ya desactivada) se
+    // reactiva como owner con la contrasena nueva y sin sesiones previas
+    let mut tx = db.begin().await?;
+    let (admin_id,): (i32,) = sqlx::query_as(
+        r#"
+        INSERT INTO admins (email, password_hash, name, role)
+        VALUES ($1, $2, 'Administrador', 'owner')
+        ON CONFLICT (email) DO UPDATE
+        SET password_hash = EXCLUDED.password_hash,
+            role = 'owner',
+            is_active = true,
+            token_version = admins.token_version + 1
+        RETURNING id
+        "#
+    )
+    .bind(&email)
+    .bind(&password_hash)
+    .fetch_one(&mut *tx)
+    .await?;
+    audit::record(
+        &mut tx,
+        &Actor::system(),
+        AuditEvent::new(AuditAction::AdminBootstrap)
+            .entity(admin_id)
+            .after(&serde_json::json!({ "email": email, "role": "owner" })),
+    ).await?;
+    tx.commit().await?;
+
+    tracing::warn!(email = %email, "Owner inicial creado, quite BOOTSTRAP_OWNER_PASSWORD del entorno");
+    Ok(())
+}
+
+// email normalizado y contrasena con la misma politica que las invitaciones
+fn check_credentials(email: &str, password: &str, policy: &PasswordPolicy) -> ApiResult<String> {
+    let email = email.trim().to_lowercase();
+    if !email.validate_email() {
+        return Err(ApiError::Validation("BOOTSTRAP_OWNER_EMAIL no es un email valido".to_string()));
+    }
+    if password == SEED_PASSWORD {
+        return Err(ApiError::Validation("BOOTSTRAP_OWNER_PASSWORD no puede ser la contrasena por defecto".to_string()));
+    }
+    validate_password_policy(password, policy).map_err(|e| {
+        ApiError::Validation(format!(
+            "BOOTSTRAP_OWNER_PASSWORD: {}",
+            e.message.as_deref().unwrap_or("no cumple la politica de contrasenas")
+        ))
+    })?;
+    Ok(email)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credenciales_del_owner_inicial() {
+        let policy = PasswordPolicy::default();
+        assert_eq!(
+            check_credentials(" Owner@LabMedical.com ", "Clave-Segura-2024", &policy).unwrap(),
+            "owner@labmedical.com"
+        );
+        assert!(check_credentials("no-es-email", "Clave-Segura-2024", &policy).is_err());
+        assert!(check_credentials("owner@labmedical.com", "corta", &policy).is_err());
+        // la contrasena publicada nunca vuelve, aunque la politica la acepte
+        let lax = PasswordPolicy { min_length: 1, require_mixed_case: false, require_digit: false, require_symbol: false };
+        assert!(check_credentials("owner@labmedical.com", SEED_PASSWORD, &lax).is_err());
+    }
+}
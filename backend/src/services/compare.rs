@@ -0,0 +1,405 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    error::{ApiError, ApiResult},
+    models::{ComparedProduct, ComparisonResponse, ComparisonRow, Product, SpecDefinition},
+};
+
+// columnas de la comparacion, mas no entran en una pantalla
+pub const MAX_PRODUCTS: usize = 4;
+
+/// slugs separados por coma, sin vacios ni repetidos, entre 2 y MAX_PRODUCTS
+pub fn parse_slugs(raw: Option<&str>) -> ApiResult<Vec<String>> {
+    let mut slugs: Vec<String> = Vec::new();
+    for slug in raw.unwrap_or_default().split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        if !slugs.iter().any(|s| s == slug) {
+            slugs.push(slug.to_string());
+        }
+    }
+
+    if slugs.len() < 2 || slugs.len() > MAX_PRODUCTS {
+        return Err(ApiError::Validation(format!(
+            "Se comparan entre 2 y {} productos distintos", MAX_PRODUCTS
+        )));
+    }
+    Ok(slugs)
+}
+
+/// matriz de comparacion: datos generales y luego cada clave de
+/// especificaciones, primero las definidas en las categorias por posicion y
+/// despues las libres por orden alfabetico
+pub fn build(products: &[Product], definitions: &[SpecDefinition]) -> ComparisonResponse {
+    let mut rows = vec![
+        general_row("brand", "Marca", None, products, |p| p.brand.clone().into()),
+        general_row("model_number", "Modelo", None, products, |p| p.model_number.clone().into()),
+        general_row("origin_country", "Pais de origen", None, products, |p| p.origin_country.clone().into()),
+        general_row("warranty_period", "Garantia", Some("meses"), products, |p| p.warranty_period.into()),
+        general_row("registro_sanitario", "Registro sanitario", None, products, |p| p.registro_sanitario.clone().into()),
+    ];
+
+    let present: BTreeSet<&str> = products
+        .iter()
+        .filter_map(|p| p.specifications.as_object())
+        .flat_map(|specs| specs.iter().filter(|(_, v)| !v.is_null()).map(|(k, _)| k.as_str()))
+        .collect();
+
+    let mut keys: Vec<&str> = Vec::new();
+    for definition in definitions {
+        if present.contains(definition.key.as_str()) && !keys.contains(&definition.key.as_str()) {
+            keys.push(&definition.key);
+        }
+    }
+    let free: Vec<&str> = present.iter().copied().filter(|key| !keys.contains(key)).collect();
+    keys.extend(free);
+
+    for key in keys {
+        rows.push(spec_row(key, products, definitions));
+    }
+
+    ComparisonResponse {
+        products: products
+            .iter()
+            .map(|p| ComparedProduct {
+                id: p.id,
+                slug: p.slug.clone(),
+                name: p.name.clone(),
+                category_id: p.category_id,
+                image_url: p.image_url.clone(),
+            })
+            .collect(),
+        rows,
+    }
+}
+
+fn general_row(
+    key: &str,
+    label: &str,
+    unit: Option<&str>,
+    products: &[Product],
+    value: impl Fn(&Product) -> serde_json::Value,
+) -> ComparisonRow {
+    let values: Vec<serde_json::Value> = products.iter().map(value).collect();
+    ComparisonRow {
+        key: key.to_string(),
+        label: label.to_string(),
+        unit: unit.map(str::to_string),
+        differs: differs(&values),
+        values,
+    }
+}
+
+fn spec_row(key: &str, products: &[Product], definitions: &[SpecDefinition]) -> ComparisonRow {
+    // cada producto se interpreta con la definicion de su propia categoria
+    let product_definitions: Vec<Option<&SpecDefinition>> = products
+        .iter()
+        .map(|p| definitions.iter().find(|d| Some(d.category_id) == p.category_id && d.key == key))
+        .collect();
+    let raw: Vec<serde_json::Value> = products
+        .iter()
+        .map(|p| p.specifications.get(key).cloned().unwrap_or(serde_json::Value::Null))
+        .collect();
+    let units: Vec<Option<&str>> = product_definitions
+        .iter()
+        .map(|d| d.and_then(|d| d.unit.as_deref()))
+        .collect();
+
+    let label = product_definitions
+        .iter()
+        .flatten()
+        .map(|d| d.label.clone())
+        .next()
+        .unwrap_or_else(|| key.to_string());
+    let (values, unit) = normalize(&raw, &units);
+
+    ComparisonRow {
+        key: format!("specifications.{}", key),
+        label,
+        unit,
+        differs: differs(&values),
+        values,
+    }
+}
+
+// si todos los valores son cantidades de la misma magnitud se expresan en la
+// unidad del primero ("1500 g" y "1,5 kg" quedan en g). si no, se dejan tal
+// cual con la unidad de la definicion cuando todas coinciden
+fn normalize(values: &[serde_json::Value], units: &[Option<&str>]) -> (Vec<serde_json::Value>, Option<String>) {
+    let quantities: Option<Vec<Option<(f64, Unit)>>> = values
+        .iter()
+        .zip(units)
+        .map(|(value, unit)| match value {
+            serde_json::Value::Null => Some(None),
+            value => quantity(value, *unit).map(Some),
+        })
+        .collect();
+
+    if let Some(quantities) = quantities {
+        let mut present = quantities.iter().flatten();
+        if let Some((_, target)) = present.next().copied() {
+            if present.all(|(_, unit)| unit.dimension == target.dimension) {
+                let values = quantities
+                    .iter()
+                    .map(|q| match q {
+                        Some((amount, unit)) => round(amount * unit.factor / target.factor).into(),
+                        None => serde_json::Value::Null,
+                    })
+                    .collect();
+                return (values, Some(target.symbol.to_string()));
+            }
+        }
+    }
+
+    let mut declared = units.iter().flatten();
+    let unit = match declared.next() {
+        Some(first) if declared.all(|unit| unit == first) => Some(first.to_string()),
+        _ => None,
+    };
+    (values.to_vec(), unit)
+}
+
+// valores distintos entre productos, los textos sin distinguir mayusculas ni
+// espacios alrededor. un producto sin el dato tambien cuenta como diferencia
+fn differs(values: &[serde_json::Value]) -> bool {
+    let comparable = |value: &serde_json::Value| match value {
+        serde_json::Value::String(s) => serde_json::Value::String(s.trim().to_lowercase()),
+        serde_json::Value::Number(n) => n.as_f64().map(|n| serde_json::json!(n)).unwrap_or(value.clone()),
+        other => other.clone(),
+    };
+    let mut values = values.iter().map(comparable);
+    match values.next() {
+        Some(first) => values.any(|value| value != first),
+        None => false,
+    }
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Mass,
+    Length,
+    Time,
+    Volume,
+    Power,
+    Frequency,
+    Voltage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Unit {
+    dimension: Dimension,
+    // factor a la unidad base de la magnitud (g, mm, s, ml, W, Hz, V)
+    factor: f64,
+    symbol: &'static str,
+}
+
+const fn unit(dimension: Dimension, factor: f64, symbol: &'static str) -> Unit {
+    Unit { dimension, factor, symbol }
+}
+
+// nombres y abreviaturas habituales en las fichas, en minusculas
+const UNITS: &[(&str, Unit)] = &[
+    ("mg", unit(Dimension::Mass, 0.001, "mg")),
+    ("g", unit(Dimension::Mass, 1.0, "g")),
+    ("gr", unit(Dimension::Mass, 1.0, "g")),
+    ("gramos", unit(Dimension::Mass, 1.0, "g")),
+    ("kg", unit(Dimension::Mass, 1000.0, "kg")),
+    ("kilos", unit(Dimension::Mass, 1000.0, "kg")),
+    ("kilogramos", unit(Dimension::Mass, 1000.0, "kg")),
+    ("mm", unit(Dimension::Length, 1.0, "mm")),
+    ("cm", unit(Dimension::Length, 10.0, "cm")),
+    ("m", unit(Dimension::Length, 1000.0, "m")),
+    ("metros", unit(Dimension::Length, 1000.0, "m")),
+    ("in", unit(Dimension::Length, 25.4, "in")),
+    ("\"", unit(Dimension::Length, 25.4, "in")),
+    ("pulg", unit(Dimension::Length, 25.4, "in")),
+    ("pulgadas", unit(Dimension::Length, 25.4, "in")),
+    ("s", unit(Dimension::Time, 1.0, "s")),
+    ("seg", unit(Dimension::Time, 1.0, "s")),
+    ("segundos", unit(Dimension::Time, 1.0, "s")),
+    ("min", unit(Dimension::Time, 60.0, "min")),
+    ("minutos", unit(Dimension::Time, 60.0, "min")),
+    ("h", unit(Dimension::Time, 3600.0, "h")),
+    ("hr", unit(Dimension::Time, 3600.0, "h")),
+    ("hrs", unit(Dimension::Time, 3600.0, "h")),
+    ("horas", unit(Dimension::Time, 3600.0, "h")),
+    ("ml", unit(Dimension::Volume, 1.0, "ml")),
+    ("l", unit(Dimension::Volume, 1000.0, "l")),
+    ("litros", unit(Dimension::Volume, 1000.0, "l")),
+    ("w", unit(Dimension::Power, 1.0, "W")),
+    ("kw", unit(Dimension::Power, 1000.0, "kW")),
+    ("hz", unit(Dimension::Frequency, 1.0, "Hz")),
+    ("khz", unit(Dimension::Frequency, 1_000.0, "kHz")),
+    ("mhz", unit(Dimension::Frequency, 1_000_000.0, "MHz")),
+    ("v", unit(Dimension::Voltage, 1.0, "V")),
+    ("kv", unit(Dimension::Voltage, 1000.0, "kV")),
+];
+
+fn find_unit(name: &str) -> Option<Unit> {
+    let name = name.trim().trim_end_matches('.').to_lowercase();
+    UNITS.iter().find(|(alias, _)| *alias == name).map(|(_, unit)| *unit)
+}
+
+// numero json con la unidad de la definicion, o texto "1,5 kg" / "4h". un
+// numero sin unidad reconocible no es una cantidad
+fn quantity(value: &serde_json::Value, declared: Option<&str>) -> Option<(f64, Unit)> {
+    match value {
+        serde_json::Value::Number(n) => Some((n.as_f64()?, find_unit(declared?)?)),
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            let split = s
+                .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ',' || c == '-'))
+                .unwrap_or(s.len());
+            let (number, unit) = s.split_at(split);
+            let amount: f64 = number.replace(',', ".").parse().ok()?;
+            let unit = match unit.trim() {
+                "" => find_unit(declared?)?,
+                unit => find_unit(unit)?,
+            };
+            amount.is_finite().then_some((amount, unit))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProductStatus, SpecDataType, SpecSection};
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn product(id: i32, category_id: i32, specifications: serde_json::Value) -> Product {
+        Product {
+            id,
+            name: format!("Bomba {}", id),
+            slug: format!("bomba-{}", id),
+            description: None,
+            category_id: Some(category_id),
+            brand: if id == 1 { "B. Braun".to_string() } else { "Mindray".to_string() },
+            model_number: Some(format!("BP-{}", id)),
+            origin_country: "Alemania".to_string(),
+            warranty_period: 24,
+            technical_sheet_url: None,
+            registro_sanitario: format!("DM-{}", id),
+            specifications,
+            image_url: None,
+            additional_images: json!([]),
+            regulatory_info: json!({}),
+            status: ProductStatus::Published,
+            publish_at: None,
+            unpublish_at: None,
+            version: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        }
+    }
+
+    fn definition(category_id: i32, key: &str, label: &str, unit: Option<&str>, position: i32) -> SpecDefinition {
+        SpecDefinition {
+            id: 0,
+            category_id,
+            key: key.to_string(),
+            label: label.to_string(),
+            unit: unit.map(str::to_string),
+            data_type: SpecDataType::Number,
+            section: SpecSection::Specifications,
+            required: false,
+            options: Vec::new(),
+            min_value: None,
+            max_value: None,
+            filterable: true,
+            position,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn row<'a>(comparison: &'a ComparisonResponse, key: &str) -> &'a ComparisonRow {
+        comparison.rows.iter().find(|r| r.key == key).unwrap()
+    }
+
+    #[test]
+    fn test_slugs_validos() {
+        assert_eq!(parse_slugs(Some(" a, b ,a,,c")).unwrap(), vec!["a", "b", "c"]);
+        assert!(parse_slugs(Some("a")).is_err());
+        assert!(parse_slugs(Some("a,a")).is_err());
+        assert!(parse_slugs(None).is_err());
+        assert!(parse_slugs(Some("a,b,c,d,e")).is_err());
+    }
+
+    #[test]
+    fn test_cantidades_y_unidades() {
+        assert_eq!(quantity(&json!("1,5 kg"), None), Some((1.5, find_unit("kg").unwrap())));
+        assert_eq!(quantity(&json!("4h"), None), Some((4.0, find_unit("h").unwrap())));
+        assert_eq!(quantity(&json!(4), Some("horas")), Some((4.0, find_unit("h").unwrap())));
+        assert_eq!(quantity(&json!("10"), Some("pulgadas")), Some((10.0, find_unit("in").unwrap())));
+        assert_eq!(quantity(&json!(4), None), None);
+        assert_eq!(quantity(&json!("LCD color"), None), None);
+        assert_eq!(quantity(&json!("12 canales"), None), None);
+    }
+
+    #[test]
+    fn test_filas_generales_y_diferencias() {
+        let products = vec![product(1, 1, json!({})), product(2, 1, json!({})), product(3, 1, json!({}))];
+        let comparison = build(&products, &[]);
+
+        let keys: Vec<&str> = comparison.rows.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, vec!["brand", "model_number", "origin_country", "warranty_period", "registro_sanitario"]);
+        assert!(row(&comparison, "brand").differs);
+        assert!(!row(&comparison, "origin_country").differs);
+        assert_eq!(row(&comparison, "warranty_period").unit.as_deref(), Some("meses"));
+        assert_eq!(comparison.products.iter().map(|p| p.slug.as_str()).collect::<Vec<_>>(), vec!["bomba-1", "bomba-2", "bomba-3"]);
+    }
+
+    #[test]
+    fn test_especificaciones_normalizadas() {
+        let products = vec![
+            product(1, 1, json!({"peso": "1500 g", "autonomia": 4, "pantalla": "LCD", "zeta": 1})),
+            product(2, 1, json!({"peso": "1,5 kg", "autonomia": 6, "pantalla": "lcd "})),
+            product(3, 2, json!({"peso": 2, "autonomia": 240})),
+        ];
+        let definitions = vec![
+            definition(1, "autonomia", "Autonomia", Some("h"), 0),
+            definition(2, "autonomia", "Autonomia de bateria", Some("min"), 0),
+            definition(2, "peso", "Peso", Some("kg"), 1),
+        ];
+        let comparison = build(&products, &definitions);
+
+        // primero las definidas por posicion, luego las libres
+        let keys: Vec<&str> = comparison.rows[5..].iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, vec!["specifications.autonomia", "specifications.peso", "specifications.pantalla", "specifications.zeta"]);
+
+        let autonomia = row(&comparison, "specifications.autonomia");
+        assert_eq!(autonomia.label, "Autonomia");
+        assert_eq!(autonomia.unit.as_deref(), Some("h"));
+        assert_eq!(autonomia.values, vec![json!(4.0), json!(6.0), json!(4.0)]);
+        assert!(autonomia.differs);
+
+        let peso = row(&comparison, "specifications.peso");
+        assert_eq!(peso.unit.as_deref(), Some("g"));
+        assert_eq!(peso.values, vec![json!(1500.0), json!(1500.0), json!(2000.0)]);
+
+        let pantalla = row(&comparison, "specifications.pantalla");
+        assert_eq!(pantalla.values, vec![json!("LCD"), json!("lcd "), json!(null)]);
+        assert!(pantalla.differs);
+    }
+
+    #[test]
+    fn test_mismo_valor_en_unidades_distintas_no_difiere() {
+        let products = vec![
+            product(1, 1, json!({"autonomia": 4})),
+            product(2, 2, json!({"autonomia": 240})),
+        ];
+        let definitions = vec![
+            definition(1, "autonomia", "Autonomia", Some("h"), 0),
+            definition(2, "autonomia", "Autonomia", Some("minutos"), 0),
+        ];
+        let autonomia = build(&products, &definitions).rows.pop().unwrap();
+        assert_eq!(autonomia.values, vec![json!(4.0), json!(4.0)]);
+        assert!(!autonomia.differs);
+    }
+}
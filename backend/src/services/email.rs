@@ -17,6 +17,7 @@ impl EmailService {
     }
     
     /// envia notificacion de cotizacion con plantilla html profesional
+    #[allow(clippy::too_many_arguments)]
     pub async fn send_quote_notification(
         &self,
         company_name: &str,
@@ -93,18 +94,103 @@ impl EmailService {
         );
         
         self.send_email(
+            &self.to,
             &format!("Cotización - {} (RUC: {})", company_name, ruc),
             &html_body,
             true,
         ).await
     }
     
-    async fn send_email(&self, subject: &str, body: &str, is_html: bool) -> ApiResult<()> {
+    /// envia invitacion de administrador con enlace de un solo uso
+    pub async fn send_admin_invitation(
+        &self,
+        to: &str,
+        invited_by: &str,
+        role: &str,
+        link: &str,
+        ttl_hours: i64,
+    ) -> ApiResult<()> {
+        let html_body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <style>
+        body {{ font-family: 'Segoe UI', Arial, sans-serif; margin: 0; padding: 0; background: #f5f5f5; }}
+        .container {{ max-width: 600px; margin: 20px auto; background: white; border-radius: 8px; overflow: hidden; box-shadow: 0 2px 8px rgba(0,0,0,0.1); }}
+        .header {{ background: linear-gradient(135deg, #1e40af 0%, #3b82f6 100%); color: white; padding: 24px 32px; }}
+        .header h1 {{ margin: 0; font-size: 20px; font-weight: 600; }}
+        .body {{ padding: 32px; color: #1f2937; line-height: 1.5; }}
+        .button {{ display: inline-block; background: #1e40af; color: white; padding: 12px 24px; border-radius: 6px; text-decoration: none; font-weight: 600; }}
+        .footer {{ background: #f9fafb; padding: 16px 32px; text-align: center; font-size: 12px; color: #9ca3af; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>Invitacion al panel de LabMedical</h1>
+        </div>
+        <div class="body">
+            <p>{invited_by} te invito a administrar el catalogo de LabMedical con el rol <strong>{role}</strong>.</p>
+            <p>Define tu contrasena desde el siguiente enlace. Es de un solo uso y vence en {ttl_hours} horas.</p>
+            <p><a class="button" href="{link}">Aceptar invitacion</a></p>
+            <p>Si no esperabas esta invitacion puedes ignorar este mensaje.</p>
+        </div>
+        <div class="footer">
+            Este es un mensaje automatico del panel administrativo de LabMedical.
+        </div>
+    </div>
+</body>
+</html>"#
+        );
+
+        self.send_email(to, "Invitacion al panel de LabMedical", &html_body, true).await
+    }
+
+    pub async fn send_password_reset(&self, to: &str, link: &str, ttl_minutes: i64) -> ApiResult<()> {
+        let html_body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <style>
+        body {{ font-family: 'Segoe UI', Arial, sans-serif; margin: 0; padding: 0; background: #f5f5f5; }}
+        .container {{ max-width: 600px; margin: 20px auto; background: white; border-radius: 8px; overflow: hidden; box-shadow: 0 2px 8px rgba(0,0,0,0.1); }}
+        .header {{ background: linear-gradient(135deg, #1e40af 0%, #3b82f6 100%); color: white; padding: 24px 32px; }}
+        .header h1 {{ margin: 0; font-size: 20px; font-weight: 600; }}
+        .body {{ padding: 32px; color: #1f2937; line-height: 1.5; }}
+        .button {{ display: inline-block; background: #1e40af; color: white; padding: 12px 24px; border-radius: 6px; text-decoration: none; font-weight: 600; }}
+        .footer {{ background: #f9fafb; padding: 16px 32px; text-align: center; font-size: 12px; color: #9ca3af; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>Restablecer contrasena</h1>
+        </div>
+        <div class="body">
+            <p>Recibimos una solicitud para restablecer la contrasena de tu cuenta en el panel de LabMedical.</p>
+            <p>El enlace es de un solo uso y vence en {ttl_minutes} minutos. Al usarlo se cerraran todas tus sesiones abiertas.</p>
+            <p><a class="button" href="{link}">Definir nueva contrasena</a></p>
+            <p>Si no solicitaste este cambio puedes ignorar este mensaje, tu contrasena actual sigue vigente.</p>
+        </div>
+        <div class="footer">
+            Este es un mensaje automatico del panel administrativo de LabMedical.
+        </div>
+    </div>
+</body>
+</html>"#
+        );
+
+        self.send_email(to, "Restablecer contrasena - LabMedical", &html_body, true).await
+    }
+    
+    async fn send_email(&self, to: &str, subject: &str, body: &str, is_html: bool) -> ApiResult<()> {
         let client = reqwest::Client::new();
         
         let mut payload = serde_json::json!({
             "from": self.from,
-            "to": [self.to],
+            "to": [to],
             "subject": subject,
         });
         
@@ -0,0 +1,57 @@
+// filas minimas para los tests que corren contra postgres. esos tests usan
+// #[sqlx::test], que crea una base por test con las migraciones, y se marcan
+// #[ignore] para que `cargo test` no necesite base de datos:
+// `DATABASE_URL=... cargo test -- --ignored`
+use sqlx::PgPool;
+
+use crate::models::AdminRole;
+
+/// producto publicado sin categoria, el slug se deriva del id para no chocar
+/// con los datos sembrados
+pub async fn insert_product(db: &PgPool, name: &str, model_number: Option<&str>, description: &str) -> i32 {
+    let (id,): (i32,) = sqlx::query_as(
+        r#"
+        INSERT INTO products (name, slug, description, brand, model_number, registro_sanitario, status)
+        VALUES ($1, 'test-' || nextval('products_id_seq'), $2, 'Marca', $3, 'RS-TEST', 'published')
+        RETURNING id
+        "#
+    )
+    .bind(name)
+    .bind(description)
+    .bind(model_number)
+    .fetch_one(db)
+    .await
+    .unwrap();
+    id
+}
+
+pub async fn insert_category(db: &PgPool, name: &str) -> i32 {
+    let (id,): (i32,) = sqlx::query_as(
+        "INSERT INTO categories (name, slug) VALUES ($1, 'test-' || nextval('categories_id_seq')) RETURNING id"
+    )
+    .bind(name)
+    .fetch_one(db)
+    .await
+    .unwrap();
+    id
+}
+
+/// administrador activo sin contrasena utilizable, los tests firman su jwt
+pub async fn insert_admin(db: &PgPool, role: AdminRole) -> i32 {
+    let (id,): (i32,) = sqlx::query_as(
+        "INSERT INTO admins (email, password_hash, role) \
+         VALUES ($1 || '-' || nextval('admins_id_seq') || '@test.local', 'sin-hash', $1) RETURNING id"
+    )
+    .bind(role.as_str())
+    .fetch_one(db)
+    .await
+    .unwrap();
+    id
+}
+
+/// el parser de texto completo solo reconoce letras fuera de ascii con un
+/// lc_ctype utf-8, como en la imagen oficial de postgres
+pub async fn require_utf8(db: &PgPool) {
+    let (ctype,): (String,) = sqlx::query_as("SELECT current_setting('lc_ctype')").fetch_one(db).await.unwrap();
+    assert!(ctype.to_lowercase().contains("utf"), "la base de prueba usa lc_ctype {}, se necesita utf-8", ctype);
+}
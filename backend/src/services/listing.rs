@@ -0,0 +1,671 @@
+use axum::http::{header, HeaderMap, HeaderValue, Uri};
+use chrono::{DateTime, Utc};
+use data_encoding::BASE64URL_NOPAD;
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::{
+    error::{ApiError, ApiResult},
+    models::PageMeta,
+};
+
+// constructor de consultas de listado. el sql solo sale de literales &'static str
+// escritos en el codigo, todo valor recibido del cliente viaja como parametro
+// enlazado ($1, $2...) y nunca se interpola en el texto de la consulta
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i32),
+    BigInt(i64),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+    Timestamp(DateTime<Utc>),
+    IntList(Vec<i32>),
+    TextList(Vec<String>),
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::BigInt(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<DateTime<Utc>> for Value {
+    fn from(value: DateTime<Utc>) -> Self {
+        Value::Timestamp(value)
+    }
+}
+
+// postgres rechaza el caracter nulo en text, se descarta para que una entrada
+// con \0 no termine en un error 500
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.replace('\0', ""))
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::from(value.as_str())
+    }
+}
+
+impl From<Vec<i32>> for Value {
+    fn from(value: Vec<i32>) -> Self {
+        Value::IntList(value)
+    }
+}
+
+impl From<Vec<String>> for Value {
+    fn from(value: Vec<String>) -> Self {
+        Value::TextList(value.into_iter().map(|v| v.replace('\0', "")).collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Sql(&'static str),
+    Bind(Value),
+}
+
+/// condicion de un WHERE, compuesta por fragmentos sql fijos y valores enlazados
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    parts: Vec<Part>,
+}
+
+impl Filter {
+    /// condicion libre para subconsultas, `sql` solo acepta literales
+    pub fn sql(fragment: &'static str) -> Self {
+        Self { parts: vec![Part::Sql(fragment)] }
+    }
+
+    pub fn and_sql(mut self, fragment: &'static str) -> Self {
+        self.parts.push(Part::Sql(fragment));
+        self
+    }
+
+    pub fn bind(mut self, value: impl Into<Value>) -> Self {
+        self.parts.push(Part::Bind(value.into()));
+        self
+    }
+
+    /// `column = $n`
+    pub fn eq(column: &'static str, value: impl Into<Value>) -> Self {
+        Self::sql(column).and_sql(" = ").bind(value)
+    }
+
+    /// `column >= $n`
+    pub fn gte(column: &'static str, value: impl Into<Value>) -> Self {
+        Self::sql(column).and_sql(" >= ").bind(value)
+    }
+
+    /// `column < $n`
+    pub fn lt(column: &'static str, value: impl Into<Value>) -> Self {
+        Self::sql(column).and_sql(" < ").bind(value)
+    }
+
+    /// `column = ANY($n)` para filtros con varios valores, la lista viaja como
+    /// un solo parametro de tipo arreglo
+    pub fn any(column: &'static str, values: impl Into<Value>) -> Self {
+        Self::sql(column).and_sql(" = ANY(").bind(values).and_sql(")")
+    }
+
+    fn push_to(&self, qb: &mut QueryBuilder<'_, Postgres>) {
+        for part in &self.parts {
+            match part {
+                Part::Sql(fragment) => {
+                    qb.push(*fragment);
+                }
+                Part::Bind(Value::Int(v)) => {
+                    qb.push_bind(*v);
+                }
+                Part::Bind(Value::BigInt(v)) => {
+                    qb.push_bind(*v);
+                }
+                Part::Bind(Value::Float(v)) => {
+                    qb.push_bind(*v);
+                }
+                Part::Bind(Value::Bool(v)) => {
+                    qb.push_bind(*v);
+                }
+                Part::Bind(Value::Text(v)) => {
+                    qb.push_bind(v.clone());
+                }
+                Part::Bind(Value::Timestamp(v)) => {
+                    qb.push_bind(*v);
+                }
+                Part::Bind(Value::IntList(v)) => {
+                    qb.push_bind(v.clone());
+                }
+                Part::Bind(Value::TextList(v)) => {
+                    qb.push_bind(v.clone());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pagination {
+    pub page: i32,
+    pub limit: i32,
+}
+
+impl Pagination {
+    /// normaliza page y limit recibidos del cliente: page >= 1 y 1 <= limit <= max
+    pub fn new(page: Option<i32>, limit: Option<i32>, default_limit: i32, max_limit: i32) -> Self {
+        Self {
+            page: page.unwrap_or(1).max(1),
+            limit: limit.unwrap_or(default_limit).clamp(1, max_limit),
+        }
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page as i64 - 1) * self.limit as i64
+    }
+}
+
+/// tipo del valor de una clave de orden, para leerlo de vuelta desde el cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    Int,
+    Float,
+    Text,
+    Timestamp,
+    Bool,
+}
+
+/// expresion de orden de un listado paginable por cursor. la ultima clave debe
+/// ser unica (el id) para que el orden sea determinista
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub expr: &'static str,
+    pub kind: KeyKind,
+    pub descending: bool,
+    // admite nulos, que van al final en ambas direcciones
+    pub nullable: bool,
+}
+
+impl SortKey {
+    pub const fn asc(expr: &'static str, kind: KeyKind) -> Self {
+        Self { expr, kind, descending: false, nullable: false }
+    }
+
+    pub const fn desc(expr: &'static str, kind: KeyKind) -> Self {
+        Self { expr, kind, descending: true, nullable: false }
+    }
+
+    pub const fn direction(self, descending: bool) -> Self {
+        Self { descending, ..self }
+    }
+
+    pub const fn nulls_last(self) -> Self {
+        Self { nullable: true, ..self }
+    }
+}
+
+/// posicion opaca dentro de un listado: el orden con que se genero y los
+/// valores de las claves de orden de la ultima fila entregada
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    sort: String,
+    values: Vec<serde_json::Value>,
+}
+
+impl Cursor {
+    /// `key` es la columna `cursor_key` de la ultima fila
+    pub fn new(sort: &str, key: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            sort: sort.to_string(),
+            values: key.as_array()?.clone(),
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        let json = serde_json::json!({ "s": self.sort, "k": self.values });
+        BASE64URL_NOPAD.encode(json.to_string().as_bytes())
+    }
+
+    /// cursor recibido del cliente, solo vale para el mismo orden
+    pub fn decode(raw: &str, sort: &str) -> ApiResult<Self> {
+        let invalid = || ApiError::Validation("Cursor no valido".to_string());
+        let bytes = BASE64URL_NOPAD.decode(raw.trim().as_bytes()).map_err(|_| invalid())?;
+        let json: serde_json::Value = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        let (Some(cursor_sort), Some(values)) = (json["s"].as_str(), json["k"].as_array()) else {
+            return Err(invalid());
+        };
+        if cursor_sort != sort {
+            return Err(ApiError::Validation("El cursor corresponde a otro orden".to_string()));
+        }
+        Ok(Self { sort: sort.to_string(), values: values.clone() })
+    }
+
+    // valores tipados segun las claves, None para un nulo admitido
+    fn bind_values(&self, keys: &[SortKey]) -> ApiResult<Vec<Option<Value>>> {
+        let invalid = || ApiError::Validation("Cursor no valido".to_string());
+        if self.values.len() != keys.len() {
+            return Err(invalid());
+        }
+        keys.iter()
+            .zip(&self.values)
+            .map(|(key, value)| {
+                if value.is_null() {
+                    return if key.nullable { Ok(None) } else { Err(invalid()) };
+                }
+                let value = match key.kind {
+                    KeyKind::Int => value.as_i64().map(Value::BigInt),
+                    KeyKind::Float => value.as_f64().map(Value::Float),
+                    KeyKind::Text => value.as_str().map(Value::from),
+                    KeyKind::Timestamp => value
+                        .as_str()
+                        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+                        .map(|v| Value::Timestamp(v.with_timezone(&Utc))),
+                    KeyKind::Bool => value.as_bool().map(Value::Bool),
+                };
+                value.map(Some).ok_or_else(invalid)
+            })
+            .collect()
+    }
+}
+
+// filas posteriores al cursor. con todas las claves en la misma direccion y sin
+// nulos es una comparacion de filas que aprovecha los indices compuestos, si no
+// se expande en (k1 > v1) OR (k1 = v1 AND k2 > v2) OR ...
+fn keyset(keys: &[SortKey], values: &[Option<Value>]) -> Filter {
+    let uniform = keys.iter().all(|k| k.descending == keys[0].descending && !k.nullable);
+    if uniform {
+        let mut filter = Filter::sql("(");
+        for (i, key) in keys.iter().enumerate() {
+            filter = filter.and_sql(if i == 0 { "" } else { ", " }).and_sql(key.expr);
+        }
+        filter = filter.and_sql(if keys[0].descending { ") < (" } else { ") > (" });
+        for (i, value) in values.iter().flatten().enumerate() {
+            filter = filter.and_sql(if i == 0 { "" } else { ", " }).bind(value.clone());
+        }
+        return filter.and_sql(")");
+    }
+
+    let mut filter = Filter::sql("(false");
+    for (i, (key, value)) in keys.iter().zip(values).enumerate() {
+        // despues de un nulo solo siguen otros nulos, lo deciden las claves siguientes
+        let Some(value) = value else {
+            continue;
+        };
+        filter = filter.and_sql(" OR (");
+        for (previous, previous_value) in keys[..i].iter().zip(values) {
+            filter = match previous_value {
+                Some(v) => filter.and_sql(previous.expr).and_sql(" = ").bind(v.clone()).and_sql(" AND "),
+                None => filter.and_sql(previous.expr).and_sql(" IS NULL AND "),
+            };
+        }
+        filter = filter
+            .and_sql("(")
+            .and_sql(key.expr)
+            .and_sql(if key.descending { " < " } else { " > " })
+            .bind(value.clone());
+        if key.nullable {
+            filter = filter.and_sql(" OR ").and_sql(key.expr).and_sql(" IS NULL");
+        }
+        filter = filter.and_sql("))");
+    }
+    filter.and_sql(")")
+}
+
+/// consulta de listado sobre una tabla con filtros, orden y paginacion
+#[derive(Debug, Clone)]
+pub struct ListQuery {
+    from: &'static str,
+    joins: Vec<Filter>,
+    filters: Vec<Filter>,
+    group_by: Option<&'static str>,
+    order_by: Option<&'static str>,
+    sort: Vec<SortKey>,
+    after: Option<Filter>,
+    limit: Option<i64>,
+    pagination: Option<Pagination>,
+}
+
+impl ListQuery {
+    pub fn new(from: &'static str) -> Self {
+        Self {
+            from,
+            joins: Vec::new(),
+            filters: Vec::new(),
+            group_by: None,
+            order_by: None,
+            sort: Vec::new(),
+            after: None,
+            limit: None,
+            pagination: None,
+        }
+    }
+
+    /// fragmento despues de FROM para joins o funciones con parametros, por
+    /// ejemplo la consulta de texto completo que luego usan columnas y orden
+    pub fn join(mut self, join: Filter) -> Self {
+        self.joins.push(join);
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn filter_opt(self, filter: Option<Filter>) -> Self {
+        match filter {
+            Some(filter) => self.filter(filter),
+            None => self,
+        }
+    }
+
+    /// agrupacion para conteos por valor, como las facetas del catalogo
+    pub fn group_by(mut self, clause: &'static str) -> Self {
+        self.group_by = Some(clause);
+        self
+    }
+
+    /// clausula de orden fija del endpoint, por ejemplo "created_at DESC, id DESC"
+    pub fn order_by(mut self, clause: &'static str) -> Self {
+        self.order_by = Some(clause);
+        self
+    }
+
+    /// orden por claves para paginar por cursor, el select agrega la columna
+    /// `cursor_key` con los valores de cada fila
+    pub fn sort_by(mut self, keys: Vec<SortKey>) -> Self {
+        self.sort = keys;
+        self
+    }
+
+    pub fn paginate(mut self, pagination: Pagination) -> Self {
+        self.pagination = Some(pagination);
+        self
+    }
+
+    /// filas despues del cursor sin OFFSET. pide una fila de mas para saber
+    /// si hay pagina siguiente, el conteo no se ve afectado
+    pub fn after(mut self, cursor: &Cursor, limit: i32) -> ApiResult<Self> {
+        let values = cursor.bind_values(&self.sort)?;
+        self.after = Some(keyset(&self.sort, &values));
+        self.pagination = None;
+        self.limit = Some(limit as i64 + 1);
+        Ok(self)
+    }
+
+    fn push_from(&self, qb: &mut QueryBuilder<'_, Postgres>) {
+        qb.push(" FROM ").push(self.from);
+        for join in &self.joins {
+            join.push_to(qb);
+        }
+    }
+
+    fn push_where<'a>(&self, qb: &mut QueryBuilder<'_, Postgres>, filters: impl Iterator<Item = &'a Filter>) {
+        for (i, filter) in filters.enumerate() {
+            qb.push(if i == 0 { " WHERE " } else { " AND " });
+            filter.push_to(qb);
+        }
+    }
+
+    /// SELECT con filtros, orden y LIMIT/OFFSET enlazados
+    pub fn select(&self, columns: &'static str) -> QueryBuilder<'static, Postgres> {
+        let mut qb = QueryBuilder::new("SELECT ");
+        qb.push(columns);
+        if !self.sort.is_empty() {
+            qb.push(", jsonb_build_array(");
+            for (i, key) in self.sort.iter().enumerate() {
+                qb.push(if i == 0 { "" } else { ", " }).push(key.expr);
+            }
+            qb.push(") AS cursor_key");
+        }
+        self.push_from(&mut qb);
+        self.push_where(&mut qb, self.filters.iter().chain(&self.after));
+
+        if let Some(group_by) = self.group_by {
+            qb.push(" GROUP BY ").push(group_by);
+        }
+        if let Some(order_by) = self.order_by {
+            qb.push(" ORDER BY ").push(order_by);
+        } else if !self.sort.is_empty() {
+            qb.push(" ORDER BY ");
+            for (i, key) in self.sort.iter().enumerate() {
+                qb.push(if i == 0 { "" } else { ", " }).push(key.expr);
+                qb.push(match (key.descending, key.nullable) {
+                    (false, false) => " ASC",
+                    (true, false) => " DESC",
+                    (false, true) => " ASC NULLS LAST",
+                    (true, true) => " DESC NULLS LAST",
+                });
+            }
+        }
+        if let Some(pagination) = self.pagination {
+            qb.push(" LIMIT ").push_bind(pagination.limit as i64);
+            qb.push(" OFFSET ").push_bind(pagination.offset());
+        } else if let Some(limit) = self.limit {
+            qb.push(" LIMIT ").push_bind(limit);
+        }
+        qb
+    }
+
+    /// COUNT(*) con los mismos filtros, ignora orden y paginacion
+    pub fn count(&self) -> QueryBuilder<'static, Postgres> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(*)");
+        self.push_from(&mut qb);
+        self.push_where(&mut qb, self.filters.iter());
+        qb
+    }
+}
+
+/// cabecera Link (rfc 8288) con las paginas siguiente y anterior. conserva el
+/// resto de parametros de la consulta y solo reemplaza `page`
+pub fn page_links(uri: &Uri, meta: &PageMeta) -> HeaderMap {
+    let mut links = Vec::new();
+    if meta.has_next {
+        links.push(format!("<{}>; rel=\"next\"", page_uri(uri, meta.page as i64 + 1)));
+    }
+    if meta.has_prev {
+        // si la pagina pedida esta fuera de rango la anterior es la ultima que existe
+        let prev = (meta.page as i64 - 1).min(meta.total_pages.max(1));
+        links.push(format!("<{}>; rel=\"prev\"", page_uri(uri, prev)));
+    }
+
+    let mut headers = HeaderMap::new();
+    if !links.is_empty() {
+        if let Ok(value) = HeaderValue::from_str(&links.join(", ")) {
+            headers.insert(header::LINK, value);
+        }
+    }
+    headers
+}
+
+/// cabecera Link con la pagina siguiente por cursor, reemplaza `cursor` y quita
+/// `page`
+pub fn cursor_links(uri: &Uri, next_cursor: Option<&str>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(cursor) = next_cursor {
+        let link = format!("<{}>; rel=\"next\"", with_param(uri, &["page", "cursor"], &format!("cursor={}", cursor)));
+        if let Ok(value) = HeaderValue::from_str(&link) {
+            headers.insert(header::LINK, value);
+        }
+    }
+    headers
+}
+
+fn page_uri(uri: &Uri, page: i64) -> String {
+    with_param(uri, &["page"], &format!("page={}", page))
+}
+
+// la uri con los parametros `replaced` quitados y `pair` al final
+fn with_param(uri: &Uri, replaced: &[&str], pair: &str) -> String {
+    let mut pairs: Vec<&str> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|p| !p.is_empty() && !replaced.contains(&p.split('=').next().unwrap_or_default()))
+        .collect();
+    pairs.push(pair);
+
+    let target = format!("{}?{}", uri.path(), pairs.join("&"));
+    // la uri ya viene validada, pero <, > y comillas romperian la cabecera
+    let mut out = String::with_capacity(target.len());
+    for c in target.chars() {
+        match c {
+            '<' | '>' | '"' | '\\' | ' ' => out.push_str(&format!("%{:02X}", c as u32)),
+            c if c.is_ascii_control() => out.push_str(&format!("%{:02X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// entradas hostiles compartidas por los tests de los modulos que arman listados
+#[cfg(test)]
+pub mod fixtures {
+    pub const HOSTILE: &[&str] = &[
+        "' OR '1'='1",
+        "'; DROP TABLE products; --",
+        "\") OR 1=1 --",
+        "%' UNION SELECT password_hash FROM admins --",
+        "$1",
+        "\\'",
+        "100%_seguro",
+        "nul\0byte",
+        "ñandú 'ácido' \u{202e}",
+        "/* comentario */",
+    ];
+
+    /// arma el sql con cada entrada hostil y exige siempre el mismo texto, asi
+    /// ningun valor llega al sql sin pasar por un parametro enlazado
+    pub fn assert_binds_hostile(build: impl Fn(&str) -> String, expected: &str) {
+        for input in HOSTILE {
+            assert_eq!(build(input), expected, "entrada: {:?}", input);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::{assert_binds_hostile, HOSTILE};
+    use super::*;
+
+    #[test]
+    fn test_valores_hostiles_nunca_entran_al_sql() {
+        assert_binds_hostile(
+            |input| {
+                ListQuery::new("products")
+                    .join(Filter::sql(", websearch_to_tsquery('spanish', ").bind(input).and_sql(") AS query"))
+                    .filter(Filter::eq("status", input))
+                    .filter(Filter::sql("search_vector @@ query"))
+                    .filter(
+                        Filter::sql("category_id = (SELECT id FROM categories WHERE slug = ")
+                            .bind(input)
+                            .and_sql(")"),
+                    )
+                    .order_by("created_at DESC")
+                    .paginate(Pagination::new(Some(2), Some(10), 20, 100))
+                    .select("*")
+                    .into_sql()
+            },
+            "SELECT * FROM products, websearch_to_tsquery('spanish', $1) AS query \
+             WHERE status = $2 AND search_vector @@ query \
+             AND category_id = (SELECT id FROM categories WHERE slug = $3) \
+             ORDER BY created_at DESC LIMIT $4 OFFSET $5",
+        );
+    }
+
+    #[test]
+    fn test_texto_descarta_caracter_nulo() {
+        assert_eq!(Value::from("a\0b"), Value::Text("ab".to_string()));
+    }
+
+    #[test]
+    fn test_paginacion_normalizada() {
+        assert_eq!(Pagination::new(None, None, 20, 100), Pagination { page: 1, limit: 20 });
+        assert_eq!(Pagination::new(Some(-5), Some(0), 20, 100), Pagination { page: 1, limit: 1 });
+        assert_eq!(Pagination::new(Some(3), Some(1000), 20, 100), Pagination { page: 3, limit: 100 });
+        assert_eq!(Pagination::new(Some(3), Some(10), 20, 100).offset(), 20);
+        // sin desbordar i32 con paginas enormes
+        assert_eq!(Pagination::new(Some(i32::MAX), Some(100), 20, 100).offset(), (i32::MAX as i64 - 1) * 100);
+    }
+
+    #[test]
+    fn test_conteo_usa_los_mismos_filtros() {
+        let query = ListQuery::new("quotes")
+            .filter(Filter::eq("status", "pending"))
+            .filter(Filter::gte("created_at", Utc::now()))
+            .order_by("created_at DESC")
+            .paginate(Pagination::new(Some(4), Some(10), 20, 100));
+        assert_eq!(
+            query.count().into_sql(),
+            "SELECT COUNT(*) FROM quotes WHERE status = $1 AND created_at >= $2"
+        );
+    }
+
+    #[test]
+    fn test_links_de_paginacion() {
+        let uri: Uri = "/api/products?category=monitores&page=2&limit=10".parse().unwrap();
+        let headers = page_links(&uri, &PageMeta::new(2, 10, 35));
+        assert_eq!(
+            headers.get(header::LINK).unwrap(),
+            "</api/products?category=monitores&limit=10&page=3>; rel=\"next\", \
+             </api/products?category=monitores&limit=10&page=1>; rel=\"prev\""
+        );
+
+        // una sola pagina no lleva cabecera
+        let uri: Uri = "/api/products".parse().unwrap();
+        assert!(page_links(&uri, &PageMeta::new(1, 20, 5)).get(header::LINK).is_none());
+
+        // fuera de rango apunta a la ultima pagina existente
+        let uri: Uri = "/api/admin/quotes?page=9".parse().unwrap();
+        assert_eq!(
+            page_links(&uri, &PageMeta::new(9, 10, 25)).get(header::LINK).unwrap(),
+            "</api/admin/quotes?page=3>; rel=\"prev\""
+        );
+    }
+
+    #[test]
+    fn test_listas_y_agrupacion() {
+        let values: Vec<String> = HOSTILE.iter().map(|v| v.to_string()).collect();
+        let query = ListQuery::new("products")
+            .filter(Filter::any("brand", values))
+            .filter(Filter::any("warranty_period", vec![12, 24]))
+            .group_by("origin_country")
+            .order_by("count DESC")
+            .select("origin_country AS value, COUNT(*) AS count");
+        assert_eq!(
+            query.into_sql(),
+            "SELECT origin_country AS value, COUNT(*) AS count FROM products \
+             WHERE brand = ANY($1) AND warranty_period = ANY($2) \
+             GROUP BY origin_country ORDER BY count DESC"
+        );
+        assert_eq!(
+            Value::from(vec!["a\0b".to_string()]),
+            Value::TextList(vec!["ab".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_sin_filtros_no_agrega_where() {
+        let sql = ListQuery::new("categories").order_by("name ASC").select("*").into_sql();
+        assert_eq!(sql, "SELECT * FROM categories ORDER BY name ASC");
+    }
+}
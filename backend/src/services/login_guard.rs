@@ -0,0 +1,170 @@
+use chrono::{DateTime, Duration, Utc};
+use governor::{
+    clock::{Clock, DefaultClock},
+    DefaultKeyedRateLimiter, Quota,
+};
+use sqlx::PgPool;
+use std::{net::IpAddr, num::NonZeroU32, sync::Arc};
+
+use crate::{
+    config::LoginThrottle,
+    error::{ApiError, ApiResult},
+    AppState,
+};
+
+// limite de rafaga en memoria por ip, complementa el bloqueo persistente
+pub type LoginRateLimiter = Arc<DefaultKeyedRateLimiter<IpAddr>>;
+
+pub fn create_rate_limiter(policy: &LoginThrottle) -> LoginRateLimiter {
+    let per_minute = NonZeroU32::new(policy.requests_per_minute).unwrap_or(NonZeroU32::MIN);
+    Arc::new(DefaultKeyedRateLimiter::keyed(Quota::per_minute(per_minute)))
+}
+
+/// limite de rafaga por ip, compartido con las rutas publicas que envian emails
+pub fn check_burst(state: &AppState, ip: IpAddr) -> ApiResult<()> {
+    if let Err(not_until) = state.login_limiter.check_key(&ip) {
+        let wait = not_until.wait_time_from(DefaultClock::default().now());
+        tracing::warn!(ip = %ip, "Rafaga de intentos de login bloqueada");
+        return Err(ApiError::RateLimitExceeded(wait.as_secs().max(1)));
+    }
+    Ok(())
+}
+
+/// rechaza el intento si la ip supera la rafaga permitida o si el email o la ip
+/// siguen bloqueados por fallos recientes
+pub async fn check(state: &AppState, email: &str, ip: IpAddr) -> ApiResult<()> {
+    check_burst(state, ip)?;
+
+    let policy = &state.config.login_throttle;
+    let now = Utc::now();
+    let window_start = now - Duration::minutes(policy.failure_window_minutes);
+
+    // por email un login exitoso reinicia el contador
+    let (email_failures, email_last): (i64, Option<DateTime<Utc>>) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*), MAX(created_at) FROM login_attempts
+        WHERE email = $1 AND success = false AND created_at > $2
+          AND created_at > COALESCE(
+              (SELECT MAX(created_at) FROM login_attempts WHERE email = $1 AND success = true),
+              '-infinity'::timestamptz
+          )
+        "#
+    )
+    .bind(email)
+    .bind(window_start)
+    .fetch_one(&state.db)
+    .await?;
+
+    // por ip no, un atacante con una cuenta propia podria reiniciarlo
+    let (ip_failures, ip_last): (i64, Option<DateTime<Utc>>) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*), MAX(created_at) FROM login_attempts
+        WHERE ip = $1 AND success = false AND created_at > $2
+        "#
+    )
+    .bind(ip.to_string())
+    .bind(window_start)
+    .fetch_one(&state.db)
+    .await?;
+
+    let remaining = [
+        lockout_remaining(email_failures, policy.max_failures_per_email, email_last, now, policy),
+        lockout_remaining(ip_failures, policy.max_failures_per_ip, ip_last, now, policy),
+    ]
+    .into_iter()
+    .flatten()
+    .max();
+
+    if let Some(secs) = remaining {
+        tracing::warn!(email = %email, ip = %ip, retry_after = secs, "Login bloqueado temporalmente");
+        return Err(ApiError::RateLimitExceeded(secs));
+    }
+
+    Ok(())
+}
+
+pub async fn record(db: &PgPool, email: &str, ip: IpAddr, success: bool, reason: &str) -> ApiResult<()> {
+    sqlx::query("INSERT INTO login_attempts (email, ip, success, reason) VALUES ($1, $2, $3, $4)")
+        .bind(email)
+        .bind(ip.to_string())
+        .bind(success)
+        .bind(reason)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// segundos de bloqueo restantes, al llegar al umbral se bloquea lockout_base_secs
+/// y cada fallo adicional duplica el tiempo hasta lockout_max_secs
+pub fn lockout_remaining(
+    failures: i64,
+    threshold: i64,
+    last_failure: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    policy: &LoginThrottle,
+) -> Option<u64> {
+    let last_failure = last_failure?;
+    if failures < threshold {
+        return None;
+    }
+
+    let exponent = (failures - threshold).min(30) as u32;
+    let lockout_secs = policy
+        .lockout_base_secs
+        .saturating_mul(1i64 << exponent)
+        .min(policy.lockout_max_secs);
+
+    let remaining = (last_failure + Duration::seconds(lockout_secs) - now).num_seconds();
+    (remaining > 0).then_some(remaining as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> LoginThrottle {
+        LoginThrottle {
+            lockout_base_secs: 30,
+            lockout_max_secs: 600,
+            ..LoginThrottle::default()
+        }
+    }
+
+    #[test]
+    fn test_sin_bloqueo_bajo_el_umbral() {
+        let now = Utc::now();
+        assert_eq!(lockout_remaining(4, 5, Some(now), now, &policy()), None);
+        assert_eq!(lockout_remaining(0, 5, None, now, &policy()), None);
+    }
+
+    #[test]
+    fn test_backoff_exponencial() {
+        let now = Utc::now();
+        assert_eq!(lockout_remaining(5, 5, Some(now), now, &policy()), Some(30));
+        assert_eq!(lockout_remaining(6, 5, Some(now), now, &policy()), Some(60));
+        assert_eq!(lockout_remaining(7, 5, Some(now), now, &policy()), Some(120));
+        // tope maximo
+        assert_eq!(lockout_remaining(50, 5, Some(now), now, &policy()), Some(600));
+    }
+
+    #[test]
+    fn test_bloqueo_expira() {
+        let now = Utc::now();
+        let last = now - Duration::seconds(20);
+        assert_eq!(lockout_remaining(5, 5, Some(last), now, &policy()), Some(10));
+        let last = now - Duration::seconds(31);
+        assert_eq!(lockout_remaining(5, 5, Some(last), now, &policy()), None);
+    }
+
+    #[test]
+    fn test_rafaga_por_ip() {
+        let limiter = create_rate_limiter(&LoginThrottle { requests_per_minute: 2, ..policy() });
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+        assert!(limiter.check_key(&ip).is_ok());
+        assert!(limiter.check_key(&ip).is_ok());
+        assert!(limiter.check_key(&ip).is_err());
+        assert!(limiter.check_key(&other).is_ok());
+    }
+}
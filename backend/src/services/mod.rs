@@ -1,4 +1,23 @@
+pub mod audit;
 pub mod auth;
+pub mod bootstrap;
+pub mod compare;
 pub mod email;
+#[cfg(test)]
+pub mod fixtures;
+pub mod jwt_keys;
+pub mod listing;
+pub mod patch;
+pub mod product_sort;
+pub mod revisions;
+pub mod login_guard;
 pub mod s3;
-pub mod validation;
\ No newline at end of file
+pub mod search;
+pub mod session;
+pub mod specs;
+pub mod suggest;
+pub mod totp;
+pub mod trash;
+pub mod validation;
+pub mod visibility;
+pub mod workflow;
\ No newline at end of file
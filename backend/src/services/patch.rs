@@ -0,0 +1,201 @@
+use serde_json::{Map, Value};
+
+use crate::error::FieldErrors;
+
+/// tipo json esperado de un campo editable por merge patch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Integer,
+    // texto rfc 3339
+    Timestamp,
+    // columnas jsonb, null las deja vacias ({} o []) en lugar de NULL
+    Object,
+    Array,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PatchField {
+    pub name: &'static str,
+    pub kind: FieldKind,
+    // la columna admite NULL, un null en el patch la limpia
+    pub nullable: bool,
+}
+
+impl PatchField {
+    pub const fn required(name: &'static str, kind: FieldKind) -> Self {
+        Self { name, kind, nullable: false }
+    }
+
+    pub const fn optional(name: &'static str, kind: FieldKind) -> Self {
+        Self { name, kind, nullable: true }
+    }
+
+    fn accepts(&self, value: &Value) -> bool {
+        match (self.kind, value) {
+            (_, Value::Null) => self.nullable || matches!(self.kind, FieldKind::Object | FieldKind::Array),
+            (FieldKind::Text, Value::String(_)) => true,
+            (FieldKind::Integer, Value::Number(n)) => n.as_i64().is_some_and(|n| i32::try_from(n).is_ok()),
+            (FieldKind::Timestamp, Value::String(s)) => chrono::DateTime::parse_from_rfc3339(s).is_ok(),
+            (FieldKind::Object, Value::Object(_)) => true,
+            (FieldKind::Array, Value::Array(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// aplica un json merge patch (rfc 7396): los objetos se combinan clave por
+/// clave, null elimina la clave y cualquier otro valor reemplaza al anterior
+pub fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// errores por campo del patch antes de aplicarlo: claves que no se pueden
+/// editar, null en campos obligatorios y tipos que no corresponden
+pub fn check(patch: &Map<String, Value>, fields: &[PatchField]) -> FieldErrors {
+    let mut errors = FieldErrors::new();
+    for (key, value) in patch {
+        let message = match fields.iter().find(|f| f.name == key) {
+            None => "Campo no editable",
+            Some(field) if field.accepts(value) => continue,
+            Some(_) if value.is_null() => "Campo obligatorio",
+            Some(_) => "Tipo no valido",
+        };
+        errors.insert(key.clone(), message.to_string());
+    }
+    errors
+}
+
+/// errores de un reemplazo completo (PUT): los mismos de `check` y ademas
+/// toda columna editable ausente, las opcionales se limpian con null explicito
+pub fn check_complete(document: &Map<String, Value>, fields: &[PatchField]) -> FieldErrors {
+    let mut errors = check(document, fields);
+    for field in fields.iter().filter(|f| !document.contains_key(f.name)) {
+        errors.insert(field.name.to_string(), "Campo obligatorio".to_string());
+    }
+    errors
+}
+
+/// documento final: las columnas jsonb que el patch dejo sin valor vuelven a
+/// su valor vacio
+pub fn fill_empty(document: &mut Value, fields: &[PatchField]) {
+    let Value::Object(document) = document else {
+        return;
+    };
+    for field in fields {
+        let empty = match field.kind {
+            FieldKind::Object => Value::Object(Map::new()),
+            FieldKind::Array => Value::Array(Vec::new()),
+            _ => continue,
+        };
+        document.entry(field.name).or_insert(empty);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FIELDS: &[PatchField] = &[
+        PatchField::required("name", FieldKind::Text),
+        PatchField::optional("model_number", FieldKind::Text),
+        PatchField::required("warranty_period", FieldKind::Integer),
+        PatchField::required("specifications", FieldKind::Object),
+        PatchField::required("additional_images", FieldKind::Array),
+        PatchField::optional("publish_at", FieldKind::Timestamp),
+    ];
+
+    #[test]
+    fn test_ejemplos_del_rfc_7396() {
+        let cases = [
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "b"}), json!({"b": "c"}), json!({"a": "b", "b": "c"})),
+            (json!({"a": "b"}), json!({"a": null}), json!({})),
+            (json!({"a": "b", "b": "c"}), json!({"a": null}), json!({"b": "c"})),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+            (json!({"a": {"b": "c"}}), json!({"a": {"b": "d", "c": null}}), json!({"a": {"b": "d"}})),
+            (json!({"a": [{"b": "c"}]}), json!({"a": [1]}), json!({"a": [1]})),
+            (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+            (json!({"a": "b"}), json!(["c"]), json!(["c"])),
+            (json!({"a": "foo"}), json!(null), json!(null)),
+            (json!({"a": "foo"}), json!("bar"), json!("bar")),
+            (json!({"e": null}), json!({"a": 1}), json!({"e": null, "a": 1})),
+            (json!([1, 2]), json!({"a": "b", "c": null}), json!({"a": "b"})),
+            (json!({}), json!({"a": {"bb": {"ccc": null}}}), json!({"a": {"bb": {}}})),
+        ];
+        for (mut target, patch, expected) in cases {
+            merge(&mut target, &patch);
+            assert_eq!(target, expected, "patch: {}", patch);
+        }
+    }
+
+    #[test]
+    fn test_errores_por_campo() {
+        let patch = json!({
+            "name": null,
+            "model_number": null,
+            "warranty_period": "24",
+            "status": "published",
+            "specifications": null,
+            "id": 5,
+            "created_at": "2024-01-01T00:00:00Z"
+        });
+        let errors = check(patch.as_object().unwrap(), FIELDS);
+        assert_eq!(errors.get("name").map(String::as_str), Some("Campo obligatorio"));
+        assert_eq!(errors.get("warranty_period").map(String::as_str), Some("Tipo no valido"));
+        assert_eq!(errors.get("id").map(String::as_str), Some("Campo no editable"));
+        assert_eq!(errors.get("created_at").map(String::as_str), Some("Campo no editable"));
+        assert!(!errors.contains_key("model_number"));
+        assert!(!errors.contains_key("specifications"));
+        assert_eq!(errors.get("status").map(String::as_str), Some("Campo no editable"));
+
+        let complete = json!({
+            "name": "Monitor",
+            "model_number": null,
+            "warranty_period": 12,
+            "specifications": {},
+            "additional_images": [],
+            "publish_at": null
+        });
+        assert!(check_complete(complete.as_object().unwrap(), FIELDS).is_empty());
+        let partial = json!({"name": "Monitor", "status": "draft"});
+        let errors = check_complete(partial.as_object().unwrap(), FIELDS);
+        assert_eq!(errors.get("model_number").map(String::as_str), Some("Campo obligatorio"));
+        assert_eq!(errors.get("warranty_period").map(String::as_str), Some("Campo obligatorio"));
+        assert_eq!(errors.get("status").map(String::as_str), Some("Campo no editable"));
+        assert!(!errors.contains_key("name"));
+
+        let dates = json!({"publish_at": "2024-03-01T08:00:00-05:00"});
+        assert!(check(dates.as_object().unwrap(), FIELDS).is_empty());
+        let dates = json!({"publish_at": "1 de marzo"});
+        assert_eq!(check(dates.as_object().unwrap(), FIELDS).get("publish_at").map(String::as_str), Some("Tipo no valido"));
+
+        let too_big = json!({"warranty_period": 5_000_000_000i64});
+        assert!(check(too_big.as_object().unwrap(), FIELDS).contains_key("warranty_period"));
+    }
+
+    #[test]
+    fn test_jsonb_limpiado_queda_vacio() {
+        let mut document = json!({"name": "Monitor", "specifications": {"peso": 2}, "additional_images": ["a"]});
+        merge(&mut document, &json!({"specifications": null, "additional_images": null}));
+        fill_empty(&mut document, FIELDS);
+        assert_eq!(document, json!({"name": "Monitor", "specifications": {}, "additional_images": []}));
+    }
+}
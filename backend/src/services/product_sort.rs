@@ -0,0 +1,260 @@
+use std::fmt;
+
+use crate::{
+    error::{ApiError, ApiResult},
+    models::{PageMeta, ProductListItem, SpecDefinition},
+    services::{
+        listing::{Cursor, Filter, KeyKind, Pagination, SortKey},
+        specs::SpecSort,
+    },
+};
+
+/// orden de los listados de productos. todos terminan en el id para que el
+/// orden sea determinista y el cursor no salte ni repita filas
+#[derive(Debug, Clone)]
+pub enum ProductSort {
+    Newest,
+    Relevance,
+    Name { descending: bool },
+    Brand { descending: bool },
+    Warranty { descending: bool },
+    Spec(SpecSort),
+}
+
+impl ProductSort {
+    /// `sort` del cliente, con `-` delante para invertir name, brand, warranty
+    /// y spec.clave. sin orden: relevancia al buscar, si no los mas recientes
+    pub fn parse(raw: Option<&str>, searching: bool, definitions: &[SpecDefinition]) -> ApiResult<Self> {
+        let Some(raw) = raw.map(str::trim).filter(|raw| !raw.is_empty()) else {
+            return Ok(if searching { Self::Relevance } else { Self::Newest });
+        };
+        if let Some(sort) = SpecSort::parse(raw, definitions)? {
+            return Ok(Self::Spec(sort));
+        }
+
+        let (descending, field) = match raw.strip_prefix('-') {
+            Some(field) => (true, field),
+            None => (false, raw),
+        };
+        match (field, descending) {
+            ("newest", false) => Ok(Self::Newest),
+            ("relevance", false) if searching => Ok(Self::Relevance),
+            ("relevance", false) => Err(ApiError::Validation(
+                "El orden relevance requiere una busqueda".to_string(),
+            )),
+            ("name", _) => Ok(Self::Name { descending }),
+            ("brand", _) => Ok(Self::Brand { descending }),
+            ("warranty", _) => Ok(Self::Warranty { descending }),
+            _ => Err(ApiError::Validation(format!("Orden no valido: {}", raw))),
+        }
+    }
+
+    /// claves de orden, las de relevancia usan la subconsulta `search`
+    pub fn keys(&self) -> Vec<SortKey> {
+        let id = SortKey::asc("id", KeyKind::Int);
+        match self {
+            Self::Newest => vec![
+                SortKey::desc("created_at", KeyKind::Timestamp),
+                id.direction(true),
+            ],
+            // el modelo exacto siempre primero, luego texto completo y al
+            // final los resultados que solo coinciden por similitud
+            Self::Relevance => vec![
+                SortKey::desc("(search.model <> '' AND model_key = search.model)", KeyKind::Bool),
+                SortKey::desc("ts_rank(search_vector, search.query)::float8", KeyKind::Float),
+                SortKey::desc("word_similarity(search.term, search_terms)::float8", KeyKind::Float),
+                SortKey::desc("created_at", KeyKind::Timestamp),
+                id.direction(true),
+            ],
+            Self::Name { descending } => vec![
+                SortKey::asc("name", KeyKind::Text).direction(*descending),
+                id.direction(*descending),
+            ],
+            Self::Brand { descending } => vec![
+                SortKey::asc("brand", KeyKind::Text).direction(*descending),
+                SortKey::asc("name", KeyKind::Text).direction(*descending),
+                id.direction(*descending),
+            ],
+            Self::Warranty { descending } => vec![
+                SortKey::asc("warranty_period", KeyKind::Int).direction(*descending),
+                id.direction(*descending),
+            ],
+            Self::Spec(sort) => sort.keys(),
+        }
+    }
+
+    /// join que necesita el orden, solo por especificacion
+    pub fn join(&self) -> Option<Filter> {
+        match self {
+            Self::Spec(sort) => Some(sort.join()),
+            _ => None,
+        }
+    }
+
+    /// cursor recibido, excluye a `page` para no mezclar las dos paginaciones
+    pub fn cursor(&self, raw: Option<&str>, page: Option<i32>) -> ApiResult<Option<Cursor>> {
+        let Some(raw) = raw else {
+            return Ok(None);
+        };
+        if page.is_some() {
+            return Err(ApiError::Validation("Use page o cursor, no ambos".to_string()));
+        }
+        Cursor::decode(raw, &self.to_string()).map(Some)
+    }
+
+    /// pagina final con sus metadatos y el cursor de la siguiente. por cursor la
+    /// consulta trae una fila de mas que solo indica si hay siguiente
+    pub fn page(
+        &self,
+        mut products: Vec<ProductListItem>,
+        pagination: Pagination,
+        by_cursor: bool,
+        total: i64,
+    ) -> (Vec<ProductListItem>, PageMeta, Option<String>) {
+        let meta = if by_cursor {
+            let has_next = products.len() > pagination.limit as usize;
+            products.truncate(pagination.limit as usize);
+            PageMeta::cursor(pagination.limit, total, has_next)
+        } else {
+            PageMeta::new(pagination.page, pagination.limit, total)
+        };
+        let next_cursor = products
+            .last()
+            .filter(|_| meta.has_next)
+            .and_then(|last| last.cursor_key.as_ref())
+            .and_then(|key| Cursor::new(&self.to_string(), key))
+            .map(|cursor| cursor.encode());
+        (products, meta, next_cursor)
+    }
+}
+
+// forma canonica del orden, es la que guarda el cursor
+impl fmt::Display for ProductSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (descending, field) = match self {
+            Self::Newest => (false, "newest".to_string()),
+            Self::Relevance => (false, "relevance".to_string()),
+            Self::Name { descending } => (*descending, "name".to_string()),
+            Self::Brand { descending } => (*descending, "brand".to_string()),
+            Self::Warranty { descending } => (*descending, "warranty".to_string()),
+            Self::Spec(sort) => (sort.descending, format!("spec.{}", sort.key)),
+        };
+        write!(f, "{}{}", if descending { "-" } else { "" }, field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::listing::ListQuery;
+    use serde_json::json;
+
+    #[test]
+    fn test_orden_por_defecto_y_canonico() {
+        assert_eq!(ProductSort::parse(None, false, &[]).unwrap().to_string(), "newest");
+        assert_eq!(ProductSort::parse(Some(" "), true, &[]).unwrap().to_string(), "relevance");
+        assert_eq!(ProductSort::parse(Some(" -name "), false, &[]).unwrap().to_string(), "-name");
+        assert_eq!(ProductSort::parse(Some("brand"), false, &[]).unwrap().to_string(), "brand");
+        assert_eq!(ProductSort::parse(Some("-warranty"), false, &[]).unwrap().to_string(), "-warranty");
+
+        assert!(ProductSort::parse(Some("relevance"), false, &[]).is_err());
+        assert!(ProductSort::parse(Some("-newest"), false, &[]).is_err());
+        assert!(ProductSort::parse(Some("price"), false, &[]).is_err());
+        assert!(ProductSort::parse(Some("spec.peso"), false, &[]).is_err());
+    }
+
+    #[test]
+    fn test_todos_los_ordenes_terminan_en_id() {
+        for raw in ["newest", "name", "-name", "brand", "warranty", "-warranty", "relevance"] {
+            let keys = ProductSort::parse(Some(raw), true, &[]).unwrap().keys();
+            assert_eq!(keys.last().unwrap().expr, "id", "orden: {}", raw);
+        }
+    }
+
+    #[test]
+    fn test_modelo_exacto_antes_que_el_texto() {
+        let keys = ProductSort::parse(None, true, &[]).unwrap().keys();
+        let order: Vec<&str> = keys.iter().map(|key| key.expr).collect();
+        assert_eq!(
+            order,
+            vec![
+                "(search.model <> '' AND model_key = search.model)",
+                "ts_rank(search_vector, search.query)::float8",
+                "word_similarity(search.term, search_terms)::float8",
+                "created_at",
+                "id",
+            ]
+        );
+        // true ordena antes que false
+        let sql = ListQuery::new("products").sort_by(keys).select("id").into_sql();
+        assert!(sql.contains("ORDER BY (search.model <> '' AND model_key = search.model) DESC, "), "{}", sql);
+    }
+
+    #[test]
+    fn test_pagina_por_numero_y_por_cursor() {
+        let sort = ProductSort::parse(Some("-name"), false, &[]).unwrap();
+        let listing = ListQuery::new("products")
+            .filter(Filter::eq("status", "published"))
+            .sort_by(sort.keys());
+
+        let sql = listing.clone().paginate(Pagination::new(Some(2), Some(10), 20, 100)).select("*").into_sql();
+        assert_eq!(
+            sql,
+            "SELECT *, jsonb_build_array(name, id) AS cursor_key FROM products WHERE status = $1 \
+             ORDER BY name DESC, id DESC LIMIT $2 OFFSET $3"
+        );
+
+        // comparacion de filas, una fila de mas para saber si hay siguiente
+        let cursor = Cursor::new(&sort.to_string(), &json!(["Monitor", 7])).unwrap();
+        let sql = listing.after(&cursor, 10).unwrap().select("*").into_sql();
+        assert_eq!(
+            sql,
+            "SELECT *, jsonb_build_array(name, id) AS cursor_key FROM products WHERE status = $1 \
+             AND (name, id) < ($2, $3) ORDER BY name DESC, id DESC LIMIT $4"
+        );
+    }
+
+    #[test]
+    fn test_cursor_con_nulos_y_direcciones_mixtas() {
+        let relevance = ListQuery::new("products").sort_by(ProductSort::Relevance.keys());
+        let cursor = Cursor::new(
+            "relevance",
+            &json!([true, 0.5, 0.25, "2024-05-01T10:00:00.123456+00:00", 3]),
+        )
+        .unwrap();
+        let sql = relevance.after(&cursor, 20).unwrap().select("*").into_sql();
+        assert!(sql.contains("((search.model <> '' AND model_key = search.model), ts_rank(search_vector, search.query)::float8, \
+            word_similarity(search.term, search_terms)::float8, created_at, id) < ($1, $2, $3, $4, $5)"), "{}", sql);
+
+        // valor nulo en una clave que admite nulos: solo siguen otros nulos
+        let keys = vec![
+            SortKey::asc("sort_spec.value", KeyKind::Float).nulls_last(),
+            SortKey::asc("id", KeyKind::Int),
+        ];
+        let listing = ListQuery::new("products").sort_by(keys.clone());
+        let sql = listing.clone().after(&Cursor::new("spec.peso", &json!([null, 4])).unwrap(), 5).unwrap().select("id").into_sql();
+        assert!(sql.contains("WHERE (false OR (sort_spec.value IS NULL AND (id > $1))) ORDER BY"), "{}", sql);
+
+        let sql = listing.clone().after(&Cursor::new("spec.peso", &json!([1.5, 4])).unwrap(), 5).unwrap().select("id").into_sql();
+        assert!(sql.contains(
+            "WHERE (false OR ((sort_spec.value > $1 OR sort_spec.value IS NULL)) \
+             OR (sort_spec.value = $2 AND (id > $3)))"
+        ), "{}", sql);
+
+        // un nulo en una clave que no lo admite o tipos cruzados no valen
+        assert!(listing.clone().after(&Cursor::new("spec.peso", &json!([1.5, null])).unwrap(), 5).is_err());
+        assert!(listing.clone().after(&Cursor::new("spec.peso", &json!(["x", 4])).unwrap(), 5).is_err());
+        assert!(listing.after(&Cursor::new("spec.peso", &json!([1.5])).unwrap(), 5).is_err());
+    }
+
+    #[test]
+    fn test_cursor_opaco_ligado_al_orden() {
+        let cursor = Cursor::new("-name", &json!(["Monitor 'x'", 7])).unwrap();
+        let encoded = cursor.encode();
+        assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Cursor::decode(&encoded, "-name").unwrap(), cursor);
+        assert!(Cursor::decode(&encoded, "name").is_err());
+        assert!(Cursor::decode("no-es-un-cursor", "-name").is_err());
+        assert!(Cursor::decode("", "-name").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeSet;
+
+use sqlx::PgConnection;
+
+use crate::{
+    error::{ApiError, ApiResult},
+    models::{FieldChange, Product},
+    services::audit::Actor,
+};
+
+// campos de control que cambian en toda revision y no aportan al diff
+const IGNORED_FIELDS: &[&str] = &["id", "version", "created_at", "updated_at"];
+
+// el texto se guarda en product_revisions.action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevisionAction {
+    Create,
+    Update,
+    // cambio de estado del flujo de publicacion
+    Status,
+    Restore,
+    // enviar a la papelera y recuperar
+    Delete,
+    Undelete,
+}
+
+impl RevisionAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevisionAction::Create => "create",
+            RevisionAction::Update => "update",
+            RevisionAction::Status => "status",
+            RevisionAction::Restore => "restore",
+            RevisionAction::Delete => "delete",
+            RevisionAction::Undelete => "undelete",
+        }
+    }
+}
+
+/// foto del producto en su version actual. va en la misma transaccion que el
+/// cambio, igual que la auditoria
+pub async fn record(
+    conn: &mut PgConnection,
+    actor: &Actor,
+    product: &Product,
+    action: RevisionAction,
+    restored_from: Option<i32>,
+) -> ApiResult<()> {
+    let snapshot = serde_json::to_value(product).map_err(|e| ApiError::Internal(e.to_string()))?;
+    sqlx::query(
+        r#"
+        INSERT INTO product_revisions (product_id, version, action, restored_from, snapshot, author_id, author_email)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#
+    )
+    .bind(product.id)
+    .bind(product.version)
+    .bind(action.as_str())
+    .bind(restored_from)
+    .bind(&snapshot)
+    .bind(actor.admin_id)
+    .bind(&actor.email)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// cambios campo por campo entre dos fotos. los objetos (specifications,
+/// regulatory_info) se comparan clave por clave como `specifications.peso`
+pub fn diff(before: &serde_json::Value, after: &serde_json::Value) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    for field in keys(before, after) {
+        if IGNORED_FIELDS.contains(&field) {
+            continue;
+        }
+        let old = before.get(field).unwrap_or(&serde_json::Value::Null);
+        let new = after.get(field).unwrap_or(&serde_json::Value::Null);
+        if old.is_object() && new.is_object() {
+            for key in keys(old, new) {
+                push_change(&mut changes, format!("{}.{}", field, key), old.get(key), new.get(key));
+            }
+        } else {
+            push_change(&mut changes, field.to_string(), Some(old), Some(new));
+        }
+    }
+    changes
+}
+
+fn keys<'a>(a: &'a serde_json::Value, b: &'a serde_json::Value) -> BTreeSet<&'a str> {
+    a.as_object()
+        .into_iter()
+        .chain(b.as_object())
+        .flat_map(|object| object.keys().map(String::as_str))
+        .collect()
+}
+
+fn push_change(
+    changes: &mut Vec<FieldChange>,
+    field: String,
+    before: Option<&serde_json::Value>,
+    after: Option<&serde_json::Value>,
+) {
+    let before = before.cloned().unwrap_or(serde_json::Value::Null);
+    let after = after.cloned().unwrap_or(serde_json::Value::Null);
+    if before != after {
+        changes.push(FieldChange { field, before, after });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_por_campo_y_por_clave() {
+        let before = json!({
+            "id": 1,
+            "version": 2,
+            "name": "Monitor",
+            "model_number": "N1",
+            "specifications": {"peso": 2, "pantalla": "LCD"},
+            "additional_images": ["a.jpg"],
+            "updated_at": "2024-01-01T00:00:00Z"
+        });
+        let after = json!({
+            "id": 1,
+            "version": 5,
+            "name": "Monitor N1",
+            "model_number": null,
+            "specifications": {"peso": 2.5, "bateria": true},
+            "additional_images": ["a.jpg", "b.jpg"],
+            "updated_at": "2024-02-01T00:00:00Z"
+        });
+
+        let changes = diff(&before, &after);
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "additional_images",
+                "model_number",
+                "name",
+                "specifications.bateria",
+                "specifications.pantalla",
+                "specifications.peso",
+            ]
+        );
+        assert_eq!(changes[1], FieldChange { field: "model_number".to_string(), before: json!("N1"), after: json!(null) });
+        assert_eq!(changes[3].before, json!(null));
+        assert_eq!(changes[4].after, json!(null));
+    }
+
+    #[test]
+    fn test_sin_cambios_no_hay_diff() {
+        let snapshot = json!({"name": "Monitor", "specifications": {}, "version": 1});
+        let mut later = snapshot.clone();
+        later["version"] = json!(3);
+        assert!(diff(&snapshot, &later).is_empty());
+    }
+}
@@ -73,8 +73,7 @@ pub async fn upload_file(
     Ok(url)
 }
 
-#[allow(dead_code)]
-pub async fn delete_image(
+pub async fn delete_file(
     client: &Client,
     bucket: &str,
     url: &str,
@@ -0,0 +1,81 @@
+use sqlx::PgPool;
+
+use crate::{error::ApiResult, services::visibility};
+
+// palabras de la busqueda que se intentan corregir, el resto se descarta
+const MAX_WORDS: i64 = 8;
+
+/// sugerencia "quiso decir" para una busqueda sin resultados. cada palabra se
+/// reemplaza por la mas parecida (trigramas) del vocabulario del catalogo activo,
+/// devuelve None si no hay nada que corregir
+pub async fn did_you_mean(db: &PgPool, term: &str) -> ApiResult<Option<String>> {
+    let row: Option<(Option<String>, Option<bool>)> = sqlx::query_as(&suggestion_sql())
+        .bind(term.replace('\0', ""))
+        .bind(MAX_WORDS)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.and_then(|(suggestion, changed)| corrected(suggestion, changed)))
+}
+
+// repetir la busqueda tal cual no ayuda, solo se sugiere si alguna palabra
+// cambio. sin palabras la frase llega nula
+fn corrected(suggestion: Option<String>, changed: Option<bool>) -> Option<String> {
+    suggestion.filter(|_| changed == Some(true))
+}
+
+// frase corregida y si alguna palabra cambio. $1 es el termino y $2 el
+// maximo de palabras
+fn suggestion_sql() -> String {
+    format!(
+        r#"
+        WITH vocabulary AS (
+            SELECT DISTINCT word
+            FROM products, regexp_split_to_table(search_terms, '[^a-z0-9]+') AS word
+            WHERE {} AND length(word) >= 3
+        )
+        SELECT
+            string_agg(COALESCE(best.word, input.word), ' ' ORDER BY input.n),
+            bool_or(best.word IS NOT NULL AND best.word <> input.word)
+        FROM regexp_split_to_table(lower(f_unaccent($1)), '\s+') WITH ORDINALITY AS input(word, n)
+        LEFT JOIN LATERAL (
+            SELECT vocabulary.word FROM vocabulary
+            WHERE vocabulary.word % input.word
+            ORDER BY similarity(vocabulary.word, input.word) DESC, vocabulary.word
+            LIMIT 1
+        ) AS best ON true
+        WHERE input.word <> '' AND input.n <= $2
+        "#,
+        visibility::PUBLIC_PRODUCT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fixtures::{insert_product, require_utf8};
+
+    #[test]
+    fn test_sugiere_solo_si_corrige_algo() {
+        assert_eq!(corrected(Some("monitor mindray".to_string()), Some(true)), Some("monitor mindray".to_string()));
+        // todas las palabras ya estaban en el vocabulario o ninguna se parece
+        assert_eq!(corrected(Some("xyzzy".to_string()), Some(false)), None);
+        assert_eq!(corrected(None, None), None);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requiere postgres, ver services::fixtures"]
+    async fn test_quiso_decir_con_el_catalogo_visible(db: PgPool) {
+        require_utf8(&db).await;
+        insert_product(&db, "Ecógrafo portátil", Some("EBA-200"), "").await;
+        let archived = insert_product(&db, "Ventilador mecanico", None, "").await;
+        sqlx::query("UPDATE products SET status = 'archived' WHERE id = $1").bind(archived).execute(&db).await.unwrap();
+
+        let suggest = |term: &'static str| did_you_mean(&db, term);
+        assert_eq!(suggest("eccografo portatl").await.unwrap().as_deref(), Some("ecografo portatil"));
+        // sin acentos ni mayusculas ya coincide, no hay nada que corregir
+        assert_eq!(suggest("ECÓGRAFO Portátil").await.unwrap(), None);
+        // las palabras de un producto que el sitio no muestra no se sugieren
+        assert_eq!(suggest("ventilado").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    error::{ApiError, ApiResult},
+    models::{Admin, AdminInfo, LoginResponse, MfaChallengeResponse},
+    services::{
+        auth::{generate_jwt, generate_secure_token, hash_token},
+        jwt_keys::JwtKeys,
+    },
+};
+
+const MFA_CHALLENGE_TTL_MINUTES: i64 = 5;
+
+#[derive(Debug, FromRow)]
+struct RefreshTokenRow {
+    id: i32,
+    admin_id: i32,
+    family_id: Uuid,
+    expires_at: DateTime<Utc>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+/// emite un jwt de acceso y abre una nueva familia de refresh tokens
+pub async fn issue_session(db: &PgPool, config: &Config, keys: &JwtKeys, admin: &Admin) -> ApiResult<LoginResponse> {
+    let mut conn = db.acquire().await?;
+
+    // limpieza oportunista de tokens vencidos del mismo admin
+    sqlx::query("DELETE FROM admin_refresh_tokens WHERE admin_id = $1 AND expires_at < NOW()")
+        .bind(admin.id)
+        .execute(&mut *conn)
+        .await?;
+
+    let refresh_token = store_refresh_token(&mut conn, config, admin.id, Uuid::new_v4()).await?;
+    session_response(config, keys, admin, refresh_token)
+}
+
+/// desafio de un solo uso que se canjea por la sesion al presentar el segundo factor
+pub async fn create_mfa_challenge(db: &PgPool, admin_id: i32) -> ApiResult<MfaChallengeResponse> {
+    let token = generate_secure_token();
+
+    sqlx::query(
+        r#"
+        INSERT INTO admin_mfa_challenges (admin_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        "#
+    )
+    .bind(admin_id)
+    .bind(hash_token(&token))
+    .bind(Utc::now() + Duration::minutes(MFA_CHALLENGE_TTL_MINUTES))
+    .execute(db)
+    .await?;
+
+    Ok(MfaChallengeResponse {
+        mfa_required: true,
+        challenge_token: token,
+        expires_in: MFA_CHALLENGE_TTL_MINUTES * 60,
+    })
+}
+
+/// canjea un refresh token por un par nuevo, el token presentado queda revocado.
+/// si llega un token ya revocado se asume robo y se revoca toda la familia
+pub async fn rotate_refresh_token(db: &PgPool, config: &Config, keys: &JwtKeys, presented: &str) -> ApiResult<LoginResponse> {
+    let mut tx = db.begin().await?;
+
+    let row = sqlx::query_as::<_, RefreshTokenRow>(
+        r#"
+        SELECT id, admin_id, family_id, expires_at, revoked_at
+        FROM admin_refresh_tokens
+        WHERE token_hash = $1
+        FOR UPDATE
+        "#
+    )
+    .bind(hash_token(presented))
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::Auth("Refresh token invalido".to_string()))?;
+
+    if row.revoked_at.is_some() {
+        revoke_family(&mut tx, row.family_id).await?;
+        tx.commit().await?;
+        tracing::warn!(
+            admin_id = row.admin_id,
+            family_id = %row.family_id,
+            "Reutilizacion de refresh token revocado, se cierra la sesion completa"
+        );
+        return Err(ApiError::Auth("Refresh token reutilizado".to_string()));
+    }
+
+    if row.expires_at <= Utc::now() {
+        return Err(ApiError::Auth("Refresh token expirado".to_string()));
+    }
+
+    let admin = sqlx::query_as::<_, Admin>("SELECT * FROM admins WHERE id = $1")
+        .bind(row.admin_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .filter(|admin| admin.is_active)
+        .ok_or_else(|| ApiError::Auth("Administrador inexistente o desactivado".to_string()))?;
+
+    sqlx::query("UPDATE admin_refresh_tokens SET revoked_at = NOW() WHERE id = $1")
+        .bind(row.id)
+        .execute(&mut *tx)
+        .await?;
+
+    let refresh_token = store_refresh_token(&mut tx, config, admin.id, row.family_id).await?;
+    tx.commit().await?;
+
+    session_response(config, keys, &admin, refresh_token)
+}
+
+/// cierra la sesion a la que pertenece el refresh token, es idempotente
+pub async fn revoke_session(db: &PgPool, presented: &str) -> ApiResult<()> {
+    let mut conn = db.acquire().await?;
+
+    let family: Option<(Uuid,)> = sqlx::query_as(
+        "SELECT family_id FROM admin_refresh_tokens WHERE token_hash = $1"
+    )
+    .bind(hash_token(presented))
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if let Some((family_id,)) = family {
+        revoke_family(&mut conn, family_id).await?;
+    }
+
+    Ok(())
+}
+
+/// invalida todos los jwt y refresh tokens emitidos para el admin
+pub async fn revoke_all_sessions(conn: &mut PgConnection, admin_id: i32) -> ApiResult<()> {
+    sqlx::query("UPDATE admins SET token_version = token_version + 1 WHERE id = $1")
+        .bind(admin_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        "UPDATE admin_refresh_tokens SET revoked_at = NOW() WHERE admin_id = $1 AND revoked_at IS NULL"
+    )
+    .bind(admin_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn revoke_family(conn: &mut PgConnection, family_id: Uuid) -> ApiResult<()> {
+    sqlx::query(
+        "UPDATE admin_refresh_tokens SET revoked_at = NOW() WHERE family_id = $1 AND revoked_at IS NULL"
+    )
+    .bind(family_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn store_refresh_token(
+    conn: &mut PgConnection,
+    config: &Config,
+    admin_id: i32,
+    family_id: Uuid,
+) -> ApiResult<String> {
+    let token = generate_secure_token();
+
+    sqlx::query(
+        r#"
+        INSERT INTO admin_refresh_tokens (admin_id, token_hash, family_id, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#
+    )
+    .bind(admin_id)
+    .bind(hash_token(&token))
+    .bind(family_id)
+    .bind(Utc::now() + Duration::days(config.refresh_token_ttl_days))
+    .execute(conn)
+    .await?;
+
+    Ok(token)
+}
+
+fn session_response(config: &Config, keys: &JwtKeys, admin: &Admin, refresh_token: String) -> ApiResult<LoginResponse> {
+    Ok(LoginResponse {
+        token: generate_jwt(admin, config.access_token_ttl_minutes, keys)?,
+        refresh_token,
+        expires_in: config.access_token_ttl_minutes * 60,
+        admin: AdminInfo::from(admin),
+    })
+}
@@ -0,0 +1,635 @@
+use sqlx::{PgConnection, PgExecutor};
+
+use crate::{
+    error::{ApiError, ApiResult, FieldErrors},
+    models::{SpecDataType, SpecDefinition, SpecSection},
+    services::listing::{Filter, KeyKind, SortKey},
+};
+
+// condiciones por especificacion y valores por condicion `in`
+pub const MAX_SPEC_FILTERS: usize = 10;
+const MAX_SPEC_VALUES: usize = 50;
+
+/// definiciones de una categoria en el orden en que se muestran
+pub async fn for_category<'e>(db: impl PgExecutor<'e>, category_id: i32) -> ApiResult<Vec<SpecDefinition>> {
+    let definitions = sqlx::query_as::<_, SpecDefinition>(
+        "SELECT * FROM category_spec_definitions WHERE category_id = $1 ORDER BY position, key"
+    )
+    .bind(category_id)
+    .fetch_all(db)
+    .await?;
+    Ok(definitions)
+}
+
+/// definiciones de specifications de varias categorias, para comparar
+/// productos de categorias distintas
+pub async fn for_categories<'e>(db: impl PgExecutor<'e>, category_ids: &[i32]) -> ApiResult<Vec<SpecDefinition>> {
+    let definitions = sqlx::query_as::<_, SpecDefinition>(
+        r#"
+        SELECT * FROM category_spec_definitions
+        WHERE category_id = ANY($1) AND section = 'specifications'
+        ORDER BY position, key
+        "#
+    )
+    .bind(category_ids)
+    .fetch_all(db)
+    .await?;
+    Ok(definitions)
+}
+
+/// definiciones filtrables en el catalogo (las de specifications) de las
+/// categorias por slug, o de todas si no se elige ninguna
+pub async fn for_category_slugs<'e>(db: impl PgExecutor<'e>, slugs: &[String]) -> ApiResult<Vec<SpecDefinition>> {
+    let definitions = sqlx::query_as::<_, SpecDefinition>(
+        r#"
+        SELECT d.* FROM category_spec_definitions d
+        JOIN categories c ON c.id = d.category_id
+        WHERE d.section = 'specifications' AND (cardinality($1::text[]) = 0 OR c.slug = ANY($1))
+        ORDER BY d.position, d.key
+        "#
+    )
+    .bind(slugs)
+    .fetch_all(db)
+    .await?;
+    Ok(definitions)
+}
+
+/// plantilla de una categoria: sus definiciones y si admite claves libres
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub category_id: i32,
+    pub strict: bool,
+    pub definitions: Vec<SpecDefinition>,
+}
+
+/// None si la categoria no existe
+pub async fn template(conn: &mut PgConnection, category_id: i32) -> ApiResult<Option<Template>> {
+    let strict: Option<(bool,)> = sqlx::query_as("SELECT strict_specs FROM categories WHERE id = $1")
+        .bind(category_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let Some((strict,)) = strict else {
+        return Ok(None);
+    };
+
+    let definitions = for_category(&mut *conn, category_id).await?;
+    Ok(Some(Template { category_id, strict, definitions }))
+}
+
+/// errores por campo de un producto contra la plantilla de su categoria. sin
+/// categoria no hay plantilla y los valores se guardan tal cual
+pub async fn validate_product(
+    conn: &mut PgConnection,
+    category_id: Option<i32>,
+    specifications: &serde_json::Value,
+    regulatory_info: &serde_json::Value,
+) -> ApiResult<FieldErrors> {
+    let Some(category_id) = category_id else {
+        return Ok(FieldErrors::new());
+    };
+    match template(conn, category_id).await? {
+        Some(template) => Ok(template.validate(specifications, regulatory_info)),
+        None => Ok(FieldErrors::from([("category_id".to_string(), "Categoria no encontrada".to_string())])),
+    }
+}
+
+impl Template {
+    /// errores por campo de specifications y regulatory_info de un producto
+    pub fn validate(&self, specifications: &serde_json::Value, regulatory_info: &serde_json::Value) -> FieldErrors {
+        let mut errors = FieldErrors::new();
+        self.validate_section(SpecSection::Specifications, specifications, &mut errors);
+        self.validate_section(SpecSection::RegulatoryInfo, regulatory_info, &mut errors);
+        errors
+    }
+
+    fn validate_section(&self, section: SpecSection, value: &serde_json::Value, errors: &mut FieldErrors) {
+        let empty = serde_json::Map::new();
+        let values = match value {
+            serde_json::Value::Object(values) => values,
+            serde_json::Value::Null => &empty,
+            _ => {
+                errors.insert(section.to_string(), "Debe ser un objeto".to_string());
+                return;
+            }
+        };
+
+        let definitions: Vec<&SpecDefinition> = self.definitions.iter().filter(|d| d.section == section).collect();
+        for definition in &definitions {
+            let field = format!("{}.{}", section, definition.key);
+            match values.get(&definition.key) {
+                None | Some(serde_json::Value::Null) => {
+                    if definition.required {
+                        errors.insert(field, "Campo obligatorio".to_string());
+                    }
+                }
+                Some(value) => {
+                    if let Err(message) = check_value(definition, value) {
+                        errors.insert(field, message);
+                    }
+                }
+            }
+        }
+
+        // en modo estricto solo valen las claves de la plantilla, asi no
+        // conviven "peso", "peso_kg" y "Weight"
+        if self.strict {
+            for key in values.keys() {
+                if !definitions.iter().any(|d| &d.key == key) {
+                    errors.insert(
+                        format!("{}.{}", section, key),
+                        "Campo no definido en la plantilla de la categoria".to_string(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// plantilla al estilo json schema, una por seccion, para que el
+    /// formulario de administracion se arme solo
+    pub fn schema(&self) -> serde_json::Value {
+        let section_schema = |section: SpecSection| {
+            let definitions: Vec<&SpecDefinition> = self.definitions.iter().filter(|d| d.section == section).collect();
+            let mut properties = serde_json::Map::new();
+            for definition in &definitions {
+                let mut property = serde_json::json!({
+                    "title": definition.label,
+                    "x-data-type": definition.data_type,
+                    "x-filterable": definition.filterable,
+                });
+                let schema_type = match definition.data_type {
+                    SpecDataType::Number => "number",
+                    SpecDataType::Boolean => "boolean",
+                    SpecDataType::Enum | SpecDataType::Text => "string",
+                };
+                property["type"] = schema_type.into();
+                if let Some(unit) = &definition.unit {
+                    property["x-unit"] = unit.as_str().into();
+                }
+                if definition.data_type == SpecDataType::Enum {
+                    property["enum"] = definition.options.clone().into();
+                }
+                if let Some(min) = definition.min_value {
+                    property["minimum"] = min.into();
+                }
+                if let Some(max) = definition.max_value {
+                    property["maximum"] = max.into();
+                }
+                properties.insert(definition.key.clone(), property);
+            }
+            serde_json::json!({
+                "type": "object",
+                "properties": properties,
+                "required": definitions.iter().filter(|d| d.required).map(|d| d.key.as_str()).collect::<Vec<_>>(),
+                "additionalProperties": !self.strict,
+                // las claves de un objeto json no conservan orden
+                "x-order": definitions.iter().map(|d| d.key.as_str()).collect::<Vec<_>>(),
+            })
+        };
+
+        serde_json::json!({
+            "category_id": self.category_id,
+            "strict": self.strict,
+            "specifications": section_schema(SpecSection::Specifications),
+            "regulatory_info": section_schema(SpecSection::RegulatoryInfo),
+        })
+    }
+}
+
+fn check_value(definition: &SpecDefinition, value: &serde_json::Value) -> Result<(), String> {
+    match definition.data_type {
+        SpecDataType::Number => {
+            let number = value.as_f64().ok_or_else(|| "Debe ser un numero".to_string())?;
+            let unit = definition.unit.as_deref().map(|unit| format!(" {}", unit)).unwrap_or_default();
+            if let Some(min) = definition.min_value.filter(|min| number < *min) {
+                return Err(format!("Debe ser mayor o igual a {}{}", min, unit));
+            }
+            if let Some(max) = definition.max_value.filter(|max| number > *max) {
+                return Err(format!("Debe ser menor o igual a {}{}", max, unit));
+            }
+            Ok(())
+        }
+        SpecDataType::Boolean if value.is_boolean() => Ok(()),
+        SpecDataType::Boolean => Err("Debe ser true o false".to_string()),
+        SpecDataType::Text if value.is_string() => Ok(()),
+        SpecDataType::Text => Err("Debe ser texto".to_string()),
+        SpecDataType::Enum => match value.as_str() {
+            Some(v) if definition.options.iter().any(|option| option == v) => Ok(()),
+            _ => Err(format!("Debe ser uno de: {}", definition.options.join(", "))),
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecOp {
+    Eq,
+    In,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl SpecOp {
+    fn parse(op: &str) -> Option<Self> {
+        match op {
+            "eq" => Some(SpecOp::Eq),
+            "in" => Some(SpecOp::In),
+            "gt" => Some(SpecOp::Gt),
+            "gte" => Some(SpecOp::Gte),
+            "lt" => Some(SpecOp::Lt),
+            "lte" => Some(SpecOp::Lte),
+            _ => None,
+        }
+    }
+
+    // comparacion numerica, `in` no aplica a numeros
+    fn comparison(self) -> Option<&'static str> {
+        match self {
+            SpecOp::Eq => Some(" = "),
+            SpecOp::In => None,
+            SpecOp::Gt => Some(" > "),
+            SpecOp::Gte => Some(" >= "),
+            SpecOp::Lt => Some(" < "),
+            SpecOp::Lte => Some(" <= "),
+        }
+    }
+}
+
+/// condicion `clave:operador:valor` recibida en ?spec=
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecCondition {
+    pub key: String,
+    pub op: SpecOp,
+    pub value: String,
+}
+
+impl SpecCondition {
+    pub fn parse(raw: &str) -> ApiResult<Self> {
+        let invalid = || ApiError::Validation(format!("Filtro de especificacion invalido: {}, se espera clave:operador:valor", raw));
+        let mut parts = raw.splitn(3, ':');
+        let key = parts.next().map(str::trim).filter(|key| !key.is_empty()).ok_or_else(invalid)?;
+        let op = parts.next().and_then(|op| SpecOp::parse(op.trim())).ok_or_else(invalid)?;
+        let value = parts.next().map(str::trim).filter(|value| !value.is_empty()).ok_or_else(invalid)?;
+        Ok(Self { key: key.to_string(), op, value: value.to_string() })
+    }
+}
+
+/// condicion ya resuelta contra su definicion, lista para el WHERE
+#[derive(Debug, Clone)]
+pub struct SpecFilter {
+    pub key: String,
+    pub filter: Filter,
+}
+
+/// orden por una especificacion numerica o de opciones
+#[derive(Debug, Clone)]
+pub struct SpecSort {
+    pub key: String,
+    pub data_type: SpecDataType,
+    pub descending: bool,
+}
+
+impl SpecSort {
+    /// `spec.clave` ascendente, `-spec.clave` descendente. None si no es un
+    /// orden por especificacion
+    pub fn parse(raw: &str, definitions: &[SpecDefinition]) -> ApiResult<Option<Self>> {
+        let (descending, field) = match raw.trim().strip_prefix('-') {
+            Some(field) => (true, field),
+            None => (false, raw.trim()),
+        };
+        let Some(key) = field.strip_prefix("spec.") else {
+            return Ok(None);
+        };
+        let data_type = resolve(definitions, key)?;
+        if !matches!(data_type, SpecDataType::Number | SpecDataType::Enum) {
+            return Err(ApiError::Validation(format!(
+                "La especificacion {} no admite orden, solo number y enum", key
+            )));
+        }
+        Ok(Some(Self { key: key.to_string(), data_type, descending }))
+    }
+
+    /// valor por producto como `sort_spec.value`, el orden queda fijo en el codigo
+    pub fn join(&self) -> Filter {
+        let value = match self.data_type {
+            SpecDataType::Number => ", LATERAL (SELECT spec_number(specifications, ",
+            _ => ", LATERAL (SELECT (specifications ->> ",
+        };
+        Filter::sql(value).bind(self.key.clone()).and_sql(") AS value) AS sort_spec")
+    }
+
+    // los productos sin el valor siempre al final, empates por id
+    pub fn keys(&self) -> Vec<SortKey> {
+        let kind = match self.data_type {
+            SpecDataType::Number => KeyKind::Float,
+            _ => KeyKind::Text,
+        };
+        vec![
+            SortKey::asc("sort_spec.value", kind).direction(self.descending).nulls_last(),
+            SortKey::asc("id", KeyKind::Int).direction(self.descending),
+        ]
+    }
+}
+
+// tipo comun de una clave en las categorias consultadas. si dos categorias la
+// definen con tipos distintos la comparacion no tendria sentido
+fn resolve(definitions: &[SpecDefinition], key: &str) -> ApiResult<SpecDataType> {
+    let mut matching = definitions.iter().filter(|d| d.key == key);
+    let first = matching
+        .next()
+        .ok_or_else(|| ApiError::Validation(format!("Especificacion desconocida: {}", key)))?;
+    let mut filterable = first.filterable;
+    for definition in matching {
+        if definition.data_type != first.data_type {
+            return Err(ApiError::Validation(format!(
+                "La especificacion {} tiene tipos distintos segun la categoria, filtre por una categoria", key
+            )));
+        }
+        filterable |= definition.filterable;
+    }
+    if !filterable {
+        return Err(ApiError::Validation(format!("La especificacion {} no es filtrable", key)));
+    }
+    Ok(first.data_type)
+}
+
+/// convierte la condicion en sql segun el tipo definido. la clave y el valor
+/// viajan enlazados, el operador sale de una lista cerrada
+pub fn resolve_filter(definitions: &[SpecDefinition], condition: &SpecCondition) -> ApiResult<SpecFilter> {
+    let key = condition.key.clone();
+    let data_type = resolve(definitions, &key)?;
+    let unsupported = || {
+        ApiError::Validation(format!(
+            "El operador no aplica a la especificacion {} de tipo {}", key, data_type
+        ))
+    };
+
+    let filter = match (data_type, condition.op) {
+        (SpecDataType::Number, op) => {
+            let comparison = op.comparison().ok_or_else(unsupported)?;
+            let value: f64 = condition
+                .value
+                .parse()
+                .ok()
+                .filter(|value: &f64| value.is_finite())
+                .ok_or_else(|| ApiError::Validation(format!("La especificacion {} espera un numero", key)))?;
+            Filter::sql("spec_number(specifications, ")
+                .bind(key.clone())
+                .and_sql(")")
+                .and_sql(comparison)
+                .bind(value)
+        }
+        (SpecDataType::Enum, SpecOp::Eq) => Filter::sql("specifications ->> ")
+            .bind(key.clone())
+            .and_sql(" = ")
+            .bind(condition.value.clone()),
+        (SpecDataType::Enum, SpecOp::In) => {
+            let values: Vec<String> = condition
+                .value
+                .split('|')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .collect();
+            if values.len() > MAX_SPEC_VALUES {
+                return Err(ApiError::Validation(format!(
+                    "La especificacion {} admite hasta {} valores", key, MAX_SPEC_VALUES
+                )));
+            }
+            Filter::sql("specifications ->> ")
+                .bind(key.clone())
+                .and_sql(" = ANY(")
+                .bind(values)
+                .and_sql(")")
+        }
+        (SpecDataType::Boolean, SpecOp::Eq) => {
+            let value: bool = condition
+                .value
+                .parse()
+                .map_err(|_| ApiError::Validation(format!("La especificacion {} espera true o false", key)))?;
+            Filter::sql("specifications -> ")
+                .bind(key.clone())
+                .and_sql(" = to_jsonb(")
+                .bind(value)
+                .and_sql(")")
+        }
+        _ => return Err(unsupported()),
+    };
+
+    Ok(SpecFilter { key, filter })
+}
+
+/// claves numericas filtrables con rango en las facetas, una vez por clave
+pub fn range_definitions(definitions: &[SpecDefinition]) -> Vec<&SpecDefinition> {
+    let mut ranges: Vec<&SpecDefinition> = Vec::new();
+    for definition in definitions {
+        if definition.filterable
+            && definition.data_type == SpecDataType::Number
+            && !ranges.iter().any(|r| r.key == definition.key)
+            && resolve(definitions, &definition.key).is_ok()
+        {
+            ranges.push(definition);
+        }
+    }
+    ranges
+}
+
+/// valor numerico por producto como `spec_value.value` para las facetas de rango
+pub fn range_join(key: &str) -> Filter {
+    Filter::sql(", LATERAL (SELECT spec_number(specifications, ")
+        .bind(key.to_string())
+        .and_sql(") AS value) AS spec_value")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::listing::{fixtures::assert_binds_hostile, ListQuery};
+    use chrono::Utc;
+
+    fn definition(category_id: i32, key: &str, data_type: SpecDataType, options: &[&str]) -> SpecDefinition {
+        SpecDefinition {
+            id: 0,
+            category_id,
+            key: key.to_string(),
+            label: key.to_string(),
+            unit: None,
+            data_type,
+            section: SpecSection::Specifications,
+            required: false,
+            options: options.iter().map(|o| o.to_string()).collect(),
+            min_value: None,
+            max_value: None,
+            filterable: true,
+            position: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn definitions() -> Vec<SpecDefinition> {
+        vec![
+            definition(1, "autonomia", SpecDataType::Number, &[]),
+            definition(1, "modo", SpecDataType::Enum, &["adulto", "pediatrico", "neonatal"]),
+            definition(1, "wifi", SpecDataType::Boolean, &[]),
+            definition(1, "notas", SpecDataType::Text, &[]),
+        ]
+    }
+
+    fn where_sql(filter: Filter) -> String {
+        ListQuery::new("products").filter(filter).select("*").into_sql()
+    }
+
+    fn template(strict: bool, definitions: Vec<SpecDefinition>) -> Template {
+        Template { category_id: 1, strict, definitions }
+    }
+
+    fn fields(errors: FieldErrors) -> Vec<String> {
+        errors.into_keys().collect()
+    }
+
+    #[test]
+    fn test_valida_tipos_y_opciones() {
+        let template = template(false, definitions());
+        let none = serde_json::Value::Null;
+        assert!(template.validate(&serde_json::json!({"autonomia": 4.5, "modo": "adulto", "otra": "libre"}), &none).is_empty());
+        assert!(template.validate(&serde_json::json!({}), &none).is_empty());
+        assert!(template.validate(&none, &none).is_empty());
+        assert_eq!(
+            fields(template.validate(&serde_json::json!({"autonomia": "4 h", "modo": "veterinario", "wifi": "si", "notas": 3}), &none)),
+            vec!["specifications.autonomia", "specifications.modo", "specifications.notas", "specifications.wifi"]
+        );
+        assert_eq!(fields(template.validate(&serde_json::json!([1, 2]), &none)), vec!["specifications"]);
+    }
+
+    #[test]
+    fn test_obligatorios_limites_y_modo_estricto() {
+        let mut defs = definitions();
+        defs[0].required = true;
+        defs[0].min_value = Some(1.0);
+        defs[0].max_value = Some(24.0);
+        defs[0].unit = Some("h".to_string());
+        let mut registro = definition(1, "clase", SpecDataType::Enum, &["I", "IIa", "IIb", "III"]);
+        registro.section = SpecSection::RegulatoryInfo;
+        registro.required = true;
+        defs.push(registro);
+
+        let libre = template(false, defs.clone());
+        let errors = libre.validate(&serde_json::json!({"autonomia": null}), &serde_json::json!({}));
+        assert_eq!(errors["specifications.autonomia"], "Campo obligatorio");
+        assert_eq!(errors["regulatory_info.clase"], "Campo obligatorio");
+
+        let errors = libre.validate(&serde_json::json!({"autonomia": 30}), &serde_json::json!({"clase": "IIa"}));
+        assert_eq!(fields(errors.clone()), vec!["specifications.autonomia"]);
+        assert_eq!(errors["specifications.autonomia"], "Debe ser menor o igual a 24 h");
+
+        let ok = (serde_json::json!({"autonomia": 4, "Peso": "2kg"}), serde_json::json!({"clase": "III", "ce": true}));
+        assert!(libre.validate(&ok.0, &ok.1).is_empty());
+        assert_eq!(
+            fields(template(true, defs).validate(&ok.0, &ok.1)),
+            vec!["regulatory_info.ce", "specifications.Peso"]
+        );
+    }
+
+    #[test]
+    fn test_plantilla_como_json_schema() {
+        let mut defs = definitions();
+        defs[0].required = true;
+        defs[0].unit = Some("h".to_string());
+        defs[0].min_value = Some(0.0);
+        let schema = template(true, defs).schema();
+
+        let specifications = &schema["specifications"];
+        assert_eq!(specifications["type"], "object");
+        assert_eq!(specifications["additionalProperties"], false);
+        assert_eq!(specifications["required"], serde_json::json!(["autonomia"]));
+        assert_eq!(specifications["x-order"], serde_json::json!(["autonomia", "modo", "wifi", "notas"]));
+        assert_eq!(
+            specifications["properties"]["autonomia"],
+            serde_json::json!({"title": "autonomia", "type": "number", "x-data-type": "number", "x-filterable": true, "x-unit": "h", "minimum": 0.0})
+        );
+        assert_eq!(specifications["properties"]["modo"]["enum"], serde_json::json!(["adulto", "pediatrico", "neonatal"]));
+        assert_eq!(schema["regulatory_info"]["properties"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_interpreta_condiciones() {
+        assert_eq!(
+            SpecCondition::parse("autonomia:gte:4").unwrap(),
+            SpecCondition { key: "autonomia".to_string(), op: SpecOp::Gte, value: "4".to_string() }
+        );
+        // el valor puede contener dos puntos
+        assert_eq!(SpecCondition::parse("modo:eq:a:b").unwrap().value, "a:b");
+        for raw in ["autonomia", "autonomia:gte", "autonomia:gte:", ":gte:4", "autonomia:like:4"] {
+            assert!(SpecCondition::parse(raw).is_err(), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_filtros_por_tipo() {
+        let defs = definitions();
+        let resolved = |raw: &str| resolve_filter(&defs, &SpecCondition::parse(raw).unwrap());
+
+        assert_eq!(
+            where_sql(resolved("autonomia:gte:4").unwrap().filter),
+            "SELECT * FROM products WHERE spec_number(specifications, $1) >= $2"
+        );
+        assert_eq!(
+            where_sql(resolved("modo:in:adulto|neonatal").unwrap().filter),
+            "SELECT * FROM products WHERE specifications ->> $1 = ANY($2)"
+        );
+        assert_eq!(
+            where_sql(resolved("wifi:eq:true").unwrap().filter),
+            "SELECT * FROM products WHERE specifications -> $1 = to_jsonb($2)"
+        );
+        for raw in ["autonomia:in:4", "autonomia:gte:NaN", "autonomia:gte:mucho", "modo:gt:a", "wifi:eq:si", "notas:eq:x", "peso:gte:1"] {
+            assert!(resolved(raw).is_err(), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_clave_con_tipos_distintos_entre_categorias() {
+        let mut defs = definitions();
+        defs.push(definition(2, "autonomia", SpecDataType::Text, &[]));
+        let condition = SpecCondition::parse("autonomia:gte:4").unwrap();
+        assert!(resolve_filter(&defs, &condition).is_err());
+        assert!(range_definitions(&defs).is_empty());
+
+        defs.pop();
+        defs.push(definition(2, "autonomia", SpecDataType::Number, &[]));
+        assert!(resolve_filter(&defs, &condition).is_ok());
+        assert_eq!(range_definitions(&defs).len(), 1);
+    }
+
+    #[test]
+    fn test_orden_por_especificacion() {
+        let defs = definitions();
+        let sort = SpecSort::parse("-spec.autonomia", &defs).unwrap().unwrap();
+        assert!(sort.descending);
+        let sql = ListQuery::new("products").join(sort.join()).sort_by(sort.keys()).select("*").into_sql();
+        assert_eq!(
+            sql,
+            "SELECT *, jsonb_build_array(sort_spec.value, id) AS cursor_key \
+             FROM products, LATERAL (SELECT spec_number(specifications, $1) AS value) AS sort_spec \
+             ORDER BY sort_spec.value DESC NULLS LAST, id DESC"
+        );
+
+        assert!(SpecSort::parse("name", &defs).unwrap().is_none());
+        assert!(SpecSort::parse("spec.wifi", &defs).is_err());
+        assert!(SpecSort::parse("spec.peso", &defs).is_err());
+    }
+
+    #[test]
+    fn test_claves_y_valores_hostiles_van_enlazados() {
+        assert_binds_hostile(
+            |input| {
+                let mut defs = definitions();
+                defs[1].options = vec![input.to_string()];
+                defs[1].key = input.to_string();
+                let condition = SpecCondition { key: input.to_string(), op: SpecOp::Eq, value: input.to_string() };
+                where_sql(resolve_filter(&defs, &condition).unwrap().filter)
+            },
+            "SELECT * FROM products WHERE specifications ->> $1 = $2",
+        );
+    }
+}
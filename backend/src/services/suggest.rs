@@ -0,0 +1,341 @@
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+use tokio::sync::Mutex;
+
+use crate::{
+    error::ApiResult,
+    models::{Suggestion, SuggestionKind},
+    services::visibility,
+    AppState,
+};
+
+// prioridad de una clave: el texto completo empieza con la consulta o lo hace
+// una palabra interior ("signos" en "Monitor de signos vitales")
+const FULL_PREFIX: u8 = 0;
+const WORD_PREFIX: u8 = 1;
+
+/// indice en memoria para autocompletar la busqueda del catalogo. guarda claves
+/// normalizadas ordenadas y responde por busqueda binaria del prefijo, sin ir a
+/// la base de datos
+#[derive(Default)]
+pub struct SuggestIndex {
+    current: RwLock<Arc<Index>>,
+    // reconstrucciones en serie, una lenta no pisa a otra mas reciente
+    rebuild_lock: Mutex<()>,
+}
+
+#[derive(Debug, Default)]
+struct Index {
+    entries: Vec<Suggestion>,
+    // (clave, prioridad, posicion en entries), ordenado por clave
+    keys: Vec<(String, u8, usize)>,
+}
+
+pub struct ProductRow {
+    pub slug: String,
+    pub name: String,
+    pub brand: String,
+    pub model_number: Option<String>,
+}
+
+pub struct CategoryRow {
+    pub slug: String,
+    pub name: String,
+}
+
+impl SuggestIndex {
+    /// hasta `limit` sugerencias para el prefijo `query`, sin distinguir acentos
+    pub fn suggest(&self, query: &str, limit: usize) -> Vec<Suggestion> {
+        let index = self.current.read().map(|index| index.clone()).unwrap_or_default();
+        index.suggest(query, limit)
+    }
+
+    /// relee productos y categorias visibles y reemplaza el indice
+    pub async fn rebuild(&self, db: &PgPool) -> ApiResult<()> {
+        let _guard = self.rebuild_lock.lock().await;
+
+        let products: Vec<(String, String, String, Option<String>)> = sqlx::query_as(&format!(
+            "SELECT slug, name, brand, model_number FROM products WHERE {}",
+            visibility::PUBLIC_PRODUCT
+        ))
+        .fetch_all(db)
+        .await?;
+        let categories: Vec<(String, String)> = sqlx::query_as(&format!(
+            "SELECT slug, name FROM categories WHERE {}",
+            visibility::PUBLIC_CATEGORY
+        ))
+        .fetch_all(db)
+        .await?;
+
+        let products: Vec<ProductRow> = products
+            .into_iter()
+            .map(|(slug, name, brand, model_number)| ProductRow { slug, name, brand, model_number })
+            .collect();
+        let categories: Vec<CategoryRow> = categories
+            .into_iter()
+            .map(|(slug, name)| CategoryRow { slug, name })
+            .collect();
+
+        self.replace(&products, &categories);
+        tracing::debug!(productos = products.len(), categorias = categories.len(), "Indice de sugerencias reconstruido");
+        Ok(())
+    }
+
+    pub fn replace(&self, products: &[ProductRow], categories: &[CategoryRow]) {
+        let index = Arc::new(Index::build(products, categories));
+        if let Ok(mut current) = self.current.write() {
+            *current = index;
+        }
+    }
+}
+
+/// reconstruye el indice en segundo plano despues de cambiar productos o
+/// categorias, un error solo deja el indice anterior
+pub fn refresh(state: &AppState) {
+    let suggest = state.suggest.clone();
+    let db = state.db.clone();
+    tokio::spawn(async move {
+        if let Err(e) = suggest.rebuild(&db).await {
+            tracing::error!(error = ?e, "No se pudo reconstruir el indice de sugerencias");
+        }
+    });
+}
+
+impl Index {
+    fn build(products: &[ProductRow], categories: &[CategoryRow]) -> Self {
+        let mut index = Index::default();
+
+        for category in categories {
+            let entry = index.push(SuggestionKind::Category, &category.slug, &category.name);
+            index.add_text(&category.name, entry);
+        }
+
+        // una sola sugerencia por marca aunque la compartan varios productos
+        let mut brands: HashMap<String, usize> = HashMap::new();
+        for product in products {
+            let key = fold(&product.brand);
+            if key.is_empty() || brands.contains_key(&key) {
+                continue;
+            }
+            let entry = index.push(SuggestionKind::Brand, &slugify(&product.brand), product.brand.trim());
+            index.add_text(&product.brand, entry);
+            brands.insert(key, entry);
+        }
+
+        for product in products {
+            let entry = index.push(SuggestionKind::Product, &product.slug, &product.name);
+            index.add_text(&product.name, entry);
+            if let Some(model) = product.model_number.as_deref() {
+                index.add_key(fold(model), FULL_PREFIX, entry);
+                index.add_key(compact(model), FULL_PREFIX, entry);
+            }
+        }
+
+        index.keys.sort();
+        index
+    }
+
+    fn push(&mut self, kind: SuggestionKind, slug: &str, label: &str) -> usize {
+        self.entries.push(Suggestion {
+            kind,
+            slug: slug.to_string(),
+            label: label.to_string(),
+        });
+        self.entries.len() - 1
+    }
+
+    // el texto completo y cada resto a partir de una palabra interior
+    fn add_text(&mut self, text: &str, entry: usize) {
+        let folded = fold(text);
+        for (i, _) in folded.match_indices(' ') {
+            self.add_key(folded[i + 1..].to_string(), WORD_PREFIX, entry);
+        }
+        self.add_key(folded, FULL_PREFIX, entry);
+    }
+
+    fn add_key(&mut self, key: String, priority: u8, entry: usize) {
+        if !key.is_empty() {
+            self.keys.push((key, priority, entry));
+        }
+    }
+
+    fn suggest(&self, query: &str, limit: usize) -> Vec<Suggestion> {
+        let folded = fold(query);
+        if folded.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        // "eba200" tambien encuentra el modelo "EBA-200"
+        let mut prefixes = vec![folded.clone()];
+        let compacted = compact(query);
+        if !compacted.is_empty() && compacted != folded {
+            prefixes.push(compacted);
+        }
+
+        let mut best: HashMap<usize, u8> = HashMap::new();
+        for prefix in &prefixes {
+            let start = self.keys.partition_point(|(key, _, _)| key.as_str() < prefix.as_str());
+            for (key, priority, entry) in &self.keys[start..] {
+                if !key.starts_with(prefix.as_str()) {
+                    break;
+                }
+                best.entry(*entry)
+                    .and_modify(|current| *current = (*current).min(*priority))
+                    .or_insert(*priority);
+            }
+        }
+
+        let mut matches: Vec<(u8, &Suggestion)> = best
+            .into_iter()
+            .map(|(entry, priority)| (priority, &self.entries[entry]))
+            .collect();
+        matches.sort_by(|(pa, a), (pb, b)| {
+            pa.cmp(pb)
+                .then(a.kind.cmp(&b.kind))
+                .then(a.label.len().cmp(&b.label.len()))
+                .then(a.label.cmp(&b.label))
+        });
+        matches.into_iter().take(limit).map(|(_, suggestion)| suggestion.clone()).collect()
+    }
+}
+
+/// minusculas, sin acentos y con espacios simples, igual para claves y consultas
+fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for word in text.split_whitespace() {
+        if !folded.is_empty() {
+            folded.push(' ');
+        }
+        for c in word.chars().flat_map(char::to_lowercase) {
+            folded.push(match c {
+                'á' | 'à' | 'â' | 'ä' | 'ã' | 'å' => 'a',
+                'é' | 'è' | 'ê' | 'ë' => 'e',
+                'í' | 'ì' | 'î' | 'ï' => 'i',
+                'ó' | 'ò' | 'ô' | 'ö' | 'õ' => 'o',
+                'ú' | 'ù' | 'û' | 'ü' => 'u',
+                'ñ' => 'n',
+                'ç' => 'c',
+                c => c,
+            });
+        }
+    }
+    folded
+}
+
+// solo letras y numeros, para modelos escritos con o sin guiones
+fn compact(text: &str) -> String {
+    fold(text).chars().filter(|c| c.is_ascii_alphanumeric()).collect()
+}
+
+fn slugify(text: &str) -> String {
+    fold(text)
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn product(slug: &str, name: &str, brand: &str, model: Option<&str>) -> ProductRow {
+        ProductRow {
+            slug: slug.to_string(),
+            name: name.to_string(),
+            brand: brand.to_string(),
+            model_number: model.map(str::to_string),
+        }
+    }
+
+    fn index() -> SuggestIndex {
+        let index = SuggestIndex::default();
+        index.replace(
+            &[
+                product("monitor-n1", "Monitor de signos vitales", "Mindray", Some("BeneVision N1")),
+                product("ecografo-dp10", "Ecógrafo portátil", "Mindray", Some("DP-10")),
+                product("centrifuga-eba200", "Centrífuga de mesa", "Hettich", Some("EBA-200")),
+            ],
+            &[CategoryRow { slug: "monitores".to_string(), name: "Monitores".to_string() }],
+        );
+        index
+    }
+
+    fn labels(suggestions: &[Suggestion]) -> Vec<(SuggestionKind, &str)> {
+        suggestions.iter().map(|s| (s.kind, s.label.as_str())).collect()
+    }
+
+    #[test]
+    fn test_prefijo_en_nombre_marca_y_modelo() {
+        let index = index();
+        assert_eq!(
+            labels(&index.suggest("mon", 10)),
+            vec![(SuggestionKind::Category, "Monitores"), (SuggestionKind::Product, "Monitor de signos vitales")]
+        );
+        assert_eq!(labels(&index.suggest("mind", 10)), vec![(SuggestionKind::Brand, "Mindray")]);
+        assert_eq!(labels(&index.suggest("dp-1", 10)), vec![(SuggestionKind::Product, "Ecógrafo portátil")]);
+        assert_eq!(labels(&index.suggest("eba200", 10)), vec![(SuggestionKind::Product, "Centrífuga de mesa")]);
+    }
+
+    #[test]
+    fn test_sin_acentos_y_palabras_interiores() {
+        let index = index();
+        assert_eq!(labels(&index.suggest("ECOGRAFO", 10)), vec![(SuggestionKind::Product, "Ecógrafo portátil")]);
+        assert_eq!(labels(&index.suggest("  centrifuga  de ", 10)), vec![(SuggestionKind::Product, "Centrífuga de mesa")]);
+        assert_eq!(labels(&index.suggest("signos vit", 10)), vec![(SuggestionKind::Product, "Monitor de signos vitales")]);
+        // el inicio del nombre va antes que una palabra interior
+        let index = SuggestIndex::default();
+        index.replace(
+            &[
+                product("a", "Lampara de mesa", "X", None),
+                product("b", "Mesa quirurgica", "X", None),
+            ],
+            &[],
+        );
+        assert_eq!(index.suggest("mesa", 10)[0].label, "Mesa quirurgica");
+    }
+
+    #[test]
+    fn test_marca_una_sola_vez_con_slug() {
+        let suggestions = index().suggest("mindray", 10);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].slug, "mindray");
+        assert_eq!(slugify("Dräger Medical"), "drager-medical");
+    }
+
+    #[test]
+    fn test_limite_y_consulta_vacia() {
+        let index = index();
+        assert_eq!(index.suggest("m", 2).len(), 2);
+        assert!(index.suggest("   ", 10).is_empty());
+        assert!(index.suggest("zzz", 10).is_empty());
+        assert!(SuggestIndex::default().suggest("mon", 10).is_empty());
+    }
+
+    #[test]
+    fn test_responde_en_pocos_milisegundos() {
+        let products: Vec<ProductRow> = (0..20_000)
+            .map(|i| {
+                product(
+                    &format!("producto-{}", i),
+                    &format!("Monitor multiparametro modelo {}", i),
+                    &format!("Marca {}", i % 300),
+                    Some(&format!("MX-{}", i)),
+                )
+            })
+            .collect();
+        let index = SuggestIndex::default();
+        index.replace(&products, &[]);
+
+        let started = Instant::now();
+        for query in ["mon", "marca 1", "mx-19", "modelo 7", "multi"] {
+            assert!(!index.suggest(query, 8).is_empty());
+        }
+        // holgado para builds de depuracion, en release son microsegundos
+        assert!(started.elapsed().as_millis() < 250, "{:?}", started.elapsed());
+    }
+}
@@ -0,0 +1,169 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+// parametros estandar que entienden google authenticator, authy, 1password, etc
+const DIGITS: u32 = 6;
+const PERIOD_SECS: u64 = 30;
+// se acepta un intervalo antes y uno despues por desfase de reloj
+const SKEW_STEPS: u64 = 1;
+const RECOVERY_CODE_BYTES: usize = 10;
+
+/// secreto aleatorio de 160 bits codificado en base32 (rfc 4648 sin padding)
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+    BASE32_NOPAD.encode(&bytes)
+}
+
+/// uri otpauth:// que las apps autenticadoras leen desde un codigo qr
+pub fn provisioning_uri(secret: &str, account: &str, issuer: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(issuer),
+        DIGITS,
+        PERIOD_SECS,
+    )
+}
+
+/// valida un codigo contra el secreto y devuelve el intervalo aceptado.
+/// los intervalos iguales o anteriores a last_step se rechazan para evitar replays
+pub fn verify_code(secret: &str, code: &str, unix_time: u64, last_step: Option<i64>) -> Option<i64> {
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let current = unix_time / PERIOD_SECS;
+    (current.saturating_sub(SKEW_STEPS)..=current + SKEW_STEPS)
+        .filter(|step| last_step.is_none_or(|last| *step as i64 > last))
+        .find(|step| constant_time_eq(hotp(&key, *step, DIGITS).as_bytes(), code.as_bytes()))
+        .map(|step| step as i64)
+}
+
+/// codigos de recuperacion legibles, ej: "k7q2-m9xw-4p3t-z8nc"
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|_| {
+            let mut bytes = [0u8; RECOVERY_CODE_BYTES];
+            OsRng.fill_bytes(&mut bytes);
+            let encoded = BASE32_NOPAD.encode(&bytes).to_lowercase();
+            encoded
+                .as_bytes()
+                .chunks(4)
+                .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .collect()
+}
+
+/// normaliza un codigo de recuperacion escrito por el usuario antes de hashearlo
+pub fn normalize_recovery_code(code: &str) -> String {
+    let compact: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    compact
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+// hotp segun rfc 4226 con hmac-sha1 y truncamiento dinamico
+fn hotp(key: &[u8], counter: u64, digits: u32) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("hmac acepta claves de cualquier largo");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([hash[offset], hash[offset + 1], hash[offset + 2], hash[offset + 3]])
+        & 0x7fff_ffff;
+
+    format!("{:0width$}", binary % 10u32.pow(digits), width = digits as usize)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // vectores de prueba del apendice b de rfc 6238 (sha1)
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_vectores_rfc_6238() {
+        let vectors = [
+            (59u64, "94287082"),
+            (1111111109, "07081804"),
+            (1111111111, "14050471"),
+            (1234567890, "89005924"),
+            (2000000000, "69279037"),
+            (20000000000, "65353130"),
+        ];
+        for (time, expected) in vectors {
+            assert_eq!(hotp(RFC_SECRET, time / PERIOD_SECS, 8), expected, "t = {}", time);
+        }
+    }
+
+    #[test]
+    fn test_verifica_codigo_con_desfase() {
+        let secret = BASE32_NOPAD.encode(RFC_SECRET);
+        // 287082 son los 6 digitos finales del vector t = 59
+        assert_eq!(verify_code(&secret, "287082", 59, None), Some(1));
+        assert_eq!(verify_code(&secret, "287082", 59 + 30, None), Some(1));
+        assert_eq!(verify_code(&secret, "287082", 59 + 90, None), None);
+        assert_eq!(verify_code(&secret, "000000", 59, None), None);
+        assert_eq!(verify_code(&secret, "28708", 59, None), None);
+    }
+
+    #[test]
+    fn test_rechaza_replay() {
+        let secret = BASE32_NOPAD.encode(RFC_SECRET);
+        assert_eq!(verify_code(&secret, "287082", 59, Some(1)), None);
+        assert_eq!(verify_code(&secret, "287082", 59, Some(0)), Some(1));
+    }
+
+    #[test]
+    fn test_uri_de_aprovisionamiento() {
+        let uri = provisioning_uri("JBSWY3DPEHPK3PXP", "admin@labmedical.com", "LabMedical");
+        assert_eq!(
+            uri,
+            "otpauth://totp/LabMedical:admin@labmedical.com?secret=JBSWY3DPEHPK3PXP&issuer=LabMedical&algorithm=SHA1&digits=6&period=30"
+        );
+        assert!(provisioning_uri("S", "a b", "Lab Medical").contains("Lab%20Medical:a%20b"));
+    }
+
+    #[test]
+    fn test_codigos_de_recuperacion() {
+        let codes = generate_recovery_codes(10);
+        assert_eq!(codes.len(), 10);
+        assert_eq!(codes[0].len(), 19);
+        assert!(codes[0].split('-').all(|group| group.len() == 4));
+        assert_ne!(codes[0], codes[1]);
+        assert_eq!(normalize_recovery_code(&codes[0].to_uppercase().replace('-', " ")), codes[0]);
+    }
+}
@@ -0,0 +1,260 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+use crate::{
+    error::ApiResult,
+    models::{Category, Product},
+    services::{
+        audit::{self, Actor, AuditAction, AuditEvent},
+        s3,
+    },
+    AppState,
+};
+
+// un producto que una cotizacion referencia se queda en la papelera, la
+// cotizacion sigue mostrando su nombre
+const PURGE_PRODUCTS: &str = r#"
+    DELETE FROM products
+    WHERE deleted_at < NOW() - make_interval(days => $1::int)
+      AND NOT EXISTS (SELECT 1 FROM quotes WHERE products.id = ANY(quotes.product_ids))
+    RETURNING *
+"#;
+
+// los productos en la papelera que sobreviven a la purga sueltan la categoria
+// vencida, si no la retendrian para siempre
+const DETACH_TRASHED_PRODUCTS: &str = r#"
+    UPDATE products SET category_id = NULL
+    WHERE deleted_at IS NOT NULL
+      AND category_id IN (SELECT id FROM categories WHERE deleted_at < NOW() - make_interval(days => $1::int))
+"#;
+
+// delete_category no acepta categorias con productos, la condicion cubre los
+// asignados despues
+const PURGE_CATEGORIES: &str = r#"
+    DELETE FROM categories
+    WHERE deleted_at < NOW() - make_interval(days => $1::int)
+      AND NOT EXISTS (SELECT 1 FROM products WHERE products.category_id = categories.id)
+    RETURNING *
+"#;
+
+/// cuando la purga borrara una fila enviada a la papelera en `deleted_at`.
+/// `None` si algo la retiene: una cotizacion o productos fuera de la papelera
+pub fn purge_at(deleted_at: Option<DateTime<Utc>>, retained: bool, retention_days: i64) -> Option<DateTime<Utc>> {
+    deleted_at.filter(|_| !retained).map(|at| at + Duration::days(retention_days))
+}
+
+/// borra lo que lleva mas de TRASH_RETENTION_DAYS en la papelera. no borra
+/// productos que una cotizacion referencia ni categorias con productos, esas
+/// filas quedan en la papelera. los archivos en s3 se eliminan despues de
+/// confirmar, un fallo solo deja el archivo huerfano
+pub async fn purge(state: &AppState) -> ApiResult<()> {
+    let (products, categories) = purge_rows(&state.db, state.config.trash_retention_days).await?;
+
+    for product in &products {
+        for url in stored_files(product, &state.config.aws_s3_bucket) {
+            // otro producto puede usar el mismo archivo
+            let in_use: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM products \
+                 WHERE image_url = $1 OR technical_sheet_url = $1 OR additional_images ? $1)"
+            )
+            .bind(&url)
+            .fetch_one(&state.db)
+            .await?;
+            if in_use {
+                continue;
+            }
+            if let Err(e) = s3::delete_file(&state.s3, &state.config.aws_s3_bucket, &url).await {
+                tracing::warn!(product_id = product.id, url = %url, error = ?e, "Archivo de producto purgado no eliminado");
+            }
+        }
+    }
+
+    if !products.is_empty() || !categories.is_empty() {
+        tracing::info!(productos = products.len(), categorias = categories.len(), "Papelera purgada");
+    }
+    Ok(())
+}
+
+// borra las filas vencidas y las audita en una transaccion, devuelve lo borrado
+async fn purge_rows(db: &PgPool, retention_days: i64) -> ApiResult<(Vec<Product>, Vec<Category>)> {
+    let mut tx = db.begin().await?;
+
+    let products = sqlx::query_as::<_, Product>(PURGE_PRODUCTS)
+        .bind(retention_days)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    sqlx::query(DETACH_TRASHED_PRODUCTS)
+        .bind(retention_days)
+        .execute(&mut *tx)
+        .await?;
+
+    // despues de los productos, una categoria puede haber quedado vacia
+    let categories = sqlx::query_as::<_, Category>(PURGE_CATEGORIES)
+        .bind(retention_days)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    let actor = Actor::system();
+    for product in &products {
+        audit::record(&mut tx, &actor, AuditEvent::new(AuditAction::ProductPurge).entity(product.id).before(product)).await?;
+    }
+    for category in &categories {
+        audit::record(&mut tx, &actor, AuditEvent::new(AuditAction::CategoryPurge).entity(category.id).before(category)).await?;
+    }
+    tx.commit().await?;
+
+    Ok((products, categories))
+}
+
+// imagen, galeria y ficha tecnica subidas a nuestro bucket, sin repetir. las
+// urls externas no se tocan
+fn stored_files(product: &Product, bucket: &str) -> Vec<String> {
+    let prefix = format!("https://{}.s3.amazonaws.com/", bucket);
+    let gallery = product
+        .additional_images
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|image| image.as_str());
+
+    let mut files: Vec<String> = Vec::new();
+    for url in product.image_url.as_deref().into_iter().chain(product.technical_sheet_url.as_deref()).chain(gallery) {
+        if url.starts_with(&prefix) && !files.iter().any(|file| file == url) {
+            files.push(url.to_string());
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProductStatus;
+    use crate::services::fixtures::{insert_category, insert_product};
+    use serde_json::json;
+
+    #[test]
+    fn test_fecha_de_purga() {
+        let deleted_at = Utc::now();
+        assert_eq!(purge_at(Some(deleted_at), false, 30), Some(deleted_at + Duration::days(30)));
+        // cotizado o categoria con productos: no se purga
+        assert_eq!(purge_at(Some(deleted_at), true, 30), None);
+        assert_eq!(purge_at(None, false, 30), None);
+    }
+
+    async fn trash(db: &PgPool, table: &str, id: i32, days_ago: i32) {
+        sqlx::query(&format!("UPDATE {} SET deleted_at = NOW() - make_interval(days => $1) WHERE id = $2", table))
+            .bind(days_ago)
+            .bind(id)
+            .execute(db)
+            .await
+            .unwrap();
+    }
+
+    async fn exists(db: &PgPool, table: &str, id: i32) -> bool {
+        sqlx::query_scalar(&format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = $1)", table))
+            .bind(id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore = "requiere postgres, ver services::fixtures"]
+    async fn test_purga_lo_vencido_y_respeta_lo_retenido(db: PgPool) {
+        let empty = insert_category(&db, "Vacia").await;
+        let with_quoted = insert_category(&db, "Con cotizado").await;
+        let with_live = insert_category(&db, "Con productos").await;
+        let expired = insert_product(&db, "Vencido", None, "").await;
+        let recent = insert_product(&db, "Reciente", None, "").await;
+        let quoted = insert_product(&db, "Cotizado", None, "").await;
+        let live = insert_product(&db, "Vigente", None, "").await;
+        sqlx::query("UPDATE products SET category_id = $1 WHERE id = $2").bind(with_quoted).bind(quoted).execute(&db).await.unwrap();
+        sqlx::query("UPDATE products SET category_id = $1 WHERE id = $2").bind(with_live).bind(live).execute(&db).await.unwrap();
+        sqlx::query(
+            "INSERT INTO quotes (company_name, company_tax_id, contact_name, email, product_ids) \
+             VALUES ('Clinica', '20100070970', 'Ana', 'ana@example.com', $1)"
+        )
+        .bind(vec![quoted])
+        .execute(&db)
+        .await
+        .unwrap();
+        for (table, id, days_ago) in [
+            ("categories", empty, 40),
+            ("categories", with_quoted, 40),
+            ("categories", with_live, 40),
+            ("products", expired, 40),
+            ("products", recent, 5),
+            ("products", quoted, 40),
+        ] {
+            trash(&db, table, id, days_ago).await;
+        }
+
+        let (products, categories) = purge_rows(&db, 30).await.unwrap();
+
+        assert_eq!(products.iter().map(|p| p.id).collect::<Vec<_>>(), vec![expired]);
+        let mut purged: Vec<i32> = categories.iter().map(|c| c.id).collect();
+        purged.sort_unstable();
+        assert_eq!(purged, vec![empty, with_quoted]);
+
+        assert!(!exists(&db, "products", expired).await);
+        assert!(exists(&db, "products", recent).await);
+        // el cotizado sigue en la papelera y suelta la categoria vencida
+        let (category_id,): (Option<i32>,) = sqlx::query_as("SELECT category_id FROM products WHERE id = $1")
+            .bind(quoted)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(category_id, None);
+        // una categoria con productos fuera de la papelera no se borra
+        assert!(exists(&db, "categories", with_live).await);
+
+        let (audited,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM audit_events WHERE action IN ('product.purge', 'category.purge') AND actor_email = 'sistema'"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(audited, 3);
+    }
+
+    #[test]
+    fn test_solo_archivos_del_bucket_sin_repetir() {
+        let bucket_url = |name: &str| format!("https://labmedical.s3.amazonaws.com/products/images/{}", name);
+        let product = Product {
+            id: 1,
+            name: "Monitor".to_string(),
+            slug: "monitor".to_string(),
+            description: None,
+            category_id: None,
+            brand: "Mindray".to_string(),
+            model_number: None,
+            origin_country: "China".to_string(),
+            warranty_period: 12,
+            technical_sheet_url: Some("https://labmedical.s3.amazonaws.com/products/documents/ficha.pdf".to_string()),
+            registro_sanitario: "DM-1".to_string(),
+            specifications: json!({}),
+            image_url: Some(bucket_url("a.jpg")),
+            additional_images: json!([bucket_url("b.jpg"), bucket_url("a.jpg"), "https://cdn.example.com/c.jpg", 5]),
+            regulatory_info: json!({}),
+            status: ProductStatus::Published,
+            publish_at: None,
+            unpublish_at: None,
+            version: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: Some(Utc::now()),
+        };
+
+        assert_eq!(
+            stored_files(&product, "labmedical"),
+            vec![
+                bucket_url("a.jpg"),
+                "https://labmedical.s3.amazonaws.com/products/documents/ficha.pdf".to_string(),
+                bucket_url("b.jpg"),
+            ]
+        );
+        assert!(stored_files(&product, "otro-bucket").is_empty());
+    }
+}
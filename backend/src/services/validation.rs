@@ -1,5 +1,10 @@
 use regex::Regex;
-use std::sync::LazyLock;
+use std::{borrow::Cow, sync::LazyLock};
+use validator::{ValidationError, ValidationErrors};
+use crate::{config::PasswordPolicy, error::FieldErrors};
+
+// tope para acotar el costo de argon2 con entradas enormes
+const PASSWORD_MAX_LENGTH: usize = 128;
 
 // Regex para validar formato de ruc peruano (11 digitos)
 static RUC_REGEX: LazyLock<Regex> = LazyLock::new(|| {
@@ -45,12 +50,61 @@ pub fn validate_ruc(ruc: &str) -> bool {
     check_digit == digits[10]
 }
 
+// un mensaje por campo a partir de los errores de validator, el primero de
+// cada campo. los errores sin mensaje propio usan uno generico por regla
+pub fn field_errors(errors: &ValidationErrors) -> FieldErrors {
+    errors
+        .field_errors()
+        .into_iter()
+        .filter_map(|(field, errors)| {
+            let error = errors.first()?;
+            let message = match &error.message {
+                Some(message) => message.to_string(),
+                None => match error.code.as_ref() {
+                    "length" => "Longitud no valida".to_string(),
+                    "range" => "Valor fuera de rango".to_string(),
+                    "url" => "URL no valida".to_string(),
+                    "email" => "Email no valido".to_string(),
+                    _ => "Valor no valido".to_string(),
+                },
+            };
+            Some((field.to_string(), message))
+        })
+        .collect()
+}
+
 // Sanitiza texto para prevenir xss usando ammonia y elimina tags
 // html peligrosos manteniendo el texto limpio
 pub fn sanitize_text(input: &str) -> String {
     ammonia::clean(input)
 }
 
+// valida una contrasena nueva contra la politica configurada, se usa desde
+// validator con use_context para recibir la politica de Config
+pub fn validate_password_policy(password: &str, policy: &PasswordPolicy) -> Result<(), ValidationError> {
+    let length = password.chars().count();
+    let failure = if length < policy.min_length {
+        Some(format!("La contrasena debe tener al menos {} caracteres", policy.min_length))
+    } else if length > PASSWORD_MAX_LENGTH {
+        Some(format!("La contrasena no puede superar {} caracteres", PASSWORD_MAX_LENGTH))
+    } else if policy.require_mixed_case
+        && !(password.chars().any(char::is_uppercase) && password.chars().any(char::is_lowercase))
+    {
+        Some("La contrasena debe combinar mayusculas y minusculas".to_string())
+    } else if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        Some("La contrasena debe incluir al menos un numero".to_string())
+    } else if policy.require_symbol && password.chars().all(char::is_alphanumeric) {
+        Some("La contrasena debe incluir al menos un simbolo".to_string())
+    } else {
+        None
+    };
+
+    match failure {
+        Some(message) => Err(ValidationError::new("password_policy").with_message(Cow::from(message))),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,4 +131,46 @@ mod tests {
         assert!(!result.contains("<script>"));
         assert!(result.contains("Texto seguro"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_politica_de_contrasena() {
+        let policy = PasswordPolicy::default();
+        assert!(validate_password_policy("Corta1", &policy).is_err());
+        assert!(validate_password_policy("sinmayusculas123", &policy).is_err());
+        assert!(validate_password_policy("SinNumerosAqui", &policy).is_err());
+        assert!(validate_password_policy("LabMedical2024", &policy).is_ok());
+        assert!(validate_password_policy(&"Aa1".repeat(50), &policy).is_err());
+
+        let strict = PasswordPolicy { require_symbol: true, ..PasswordPolicy::default() };
+        assert!(validate_password_policy("LabMedical2024", &strict).is_err());
+        assert!(validate_password_policy("LabMedical-2024", &strict).is_ok());
+
+        let relaxed = PasswordPolicy {
+            min_length: 4,
+            require_mixed_case: false,
+            require_digit: false,
+            require_symbol: false,
+        };
+        assert!(validate_password_policy("abcd", &relaxed).is_ok());
+    }
+
+    #[test]
+    fn test_errores_por_campo_de_validator() {
+        use crate::models::CreateProductRequest;
+        use validator::Validate;
+
+        let request: CreateProductRequest = serde_json::from_value(serde_json::json!({
+            "name": "x",
+            "slug": "monitor",
+            "brand": "Mindray",
+            "origin_country": "China",
+            "warranty_period": 500,
+            "technical_sheet_url": "no es url",
+            "registro_sanitario": "DM-1"
+        }))
+        .unwrap();
+        let errors = field_errors(&request.validate().unwrap_err());
+        assert_eq!(errors.keys().collect::<Vec<_>>(), vec!["name", "technical_sheet_url", "warranty_period"]);
+        assert_eq!(errors["warranty_period"], "Valor fuera de rango");
+    }
+}
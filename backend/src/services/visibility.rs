@@ -0,0 +1,78 @@
+use crate::services::listing::Filter;
+
+/// productos que el sitio publico puede mostrar: publicados, dentro de su
+/// ventana de publicacion, fuera de la papelera y sin una categoria en la
+/// papelera. toda consulta de routes::public, la busqueda y las sugerencias
+/// pasan por aqui. la ventana se evalua con NOW(), los lanzamientos y retiros
+/// programados no necesitan una tarea aparte
+pub const PUBLIC_PRODUCT: &str = "(products.status = 'published' \
+    AND (products.publish_at IS NULL OR products.publish_at <= NOW()) \
+    AND (products.unpublish_at IS NULL OR products.unpublish_at > NOW()) \
+    AND products.deleted_at IS NULL \
+    AND NOT EXISTS (SELECT 1 FROM categories WHERE categories.id = products.category_id \
+    AND categories.deleted_at IS NOT NULL))";
+
+/// categorias que el sitio publico puede mostrar
+pub const PUBLIC_CATEGORY: &str = "categories.deleted_at IS NULL";
+
+pub fn public_product() -> Filter {
+    Filter::sql(PUBLIC_PRODUCT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fixtures::{insert_category, insert_product};
+    use crate::services::listing::ListQuery;
+    use sqlx::PgPool;
+
+    #[sqlx::test]
+    #[ignore = "requiere postgres, ver services::fixtures"]
+    async fn test_papelera_y_ventana_no_son_publicas(db: PgPool) {
+        let trashed_category = insert_category(&db, "En la papelera").await;
+        let visible = insert_product(&db, "Visible", None, "").await;
+        let mut hidden = Vec::new();
+        for change in [
+            "deleted_at = NOW()",
+            "category_id = $2",
+            "status = 'draft'",
+            "publish_at = NOW() + interval '1 day'",
+            "unpublish_at = NOW() - interval '1 day'",
+        ] {
+            let id = insert_product(&db, "Oculto", None, "").await;
+            sqlx::query(&format!("UPDATE products SET {} WHERE id = $1", change))
+                .bind(id)
+                .bind(trashed_category)
+                .execute(&db)
+                .await
+                .unwrap();
+            hidden.push(id);
+        }
+        sqlx::query("UPDATE categories SET deleted_at = NOW() WHERE id = $1")
+            .bind(trashed_category)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let public: Vec<(i32,)> = ListQuery::new("products")
+            .filter(public_product())
+            .order_by("id")
+            .select("id")
+            .build_query_as()
+            .fetch_all(&db)
+            .await
+            .unwrap();
+        assert_eq!(public, vec![(visible,)], "ocultos: {:?}", hidden);
+
+        let (categories,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM categories WHERE {}", PUBLIC_CATEGORY))
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let (seeded,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM categories WHERE id <> $1")
+            .bind(trashed_category)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(categories, seeded);
+    }
+}
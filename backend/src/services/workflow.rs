@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    error::{ApiError, ApiResult, FieldErrors},
+    models::{Permission, ProductStatus},
+};
+
+use ProductStatus::*;
+
+/// permiso que exige pasar de `from` a `to`. el editor envia a revision y
+/// reabre archivados como borrador, el resto es decision del aprobador
+pub fn transition(from: ProductStatus, to: ProductStatus) -> ApiResult<Permission> {
+    match (from, to) {
+        (Draft, InReview) | (Archived, Draft) => Ok(Permission::CatalogWrite),
+        (InReview, Draft) | (InReview, Published) | (Published, Archived) | (Archived, Published) => {
+            Ok(Permission::CatalogPublish)
+        }
+        _ => Err(ApiError::Validation(format!("No se puede pasar de {} a {}", from, to))),
+    }
+}
+
+/// permiso que exige editar un producto en `status`. el contenido y la
+/// ventana de un producto publicado salen al sitio sin pasar por revision,
+/// asi que solo el aprobador los cambia; el editor lo archiva y lo reabre
+/// como borrador
+pub fn edit_permission(status: ProductStatus) -> Permission {
+    match status {
+        Published => Permission::CatalogPublish,
+        Draft | InReview | Archived => Permission::CatalogWrite,
+    }
+}
+
+/// error por campo si la ventana de publicacion termina antes de empezar
+pub fn check_window(publish_at: Option<DateTime<Utc>>, unpublish_at: Option<DateTime<Utc>>) -> FieldErrors {
+    let mut errors = FieldErrors::new();
+    if let (Some(start), Some(end)) = (publish_at, unpublish_at) {
+        if end <= start {
+            errors.insert("unpublish_at".to_string(), "Debe ser posterior a publish_at".to_string());
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AdminRole;
+    use chrono::Duration;
+
+    #[test]
+    fn test_editor_envia_y_aprobador_publica() {
+        assert_eq!(transition(Draft, InReview).unwrap(), Permission::CatalogWrite);
+        assert_eq!(transition(InReview, Published).unwrap(), Permission::CatalogPublish);
+        assert_eq!(transition(InReview, Draft).unwrap(), Permission::CatalogPublish);
+        assert_eq!(transition(Published, Archived).unwrap(), Permission::CatalogPublish);
+        assert_eq!(transition(Archived, Published).unwrap(), Permission::CatalogPublish);
+        assert_eq!(transition(Archived, Draft).unwrap(), Permission::CatalogWrite);
+
+        // un borrador no se publica sin pasar por revision
+        for (from, to) in [(Draft, Published), (Draft, Archived), (Published, Draft), (Published, Published)] {
+            assert!(transition(from, to).is_err(), "{} -> {}", from, to);
+        }
+    }
+
+    #[test]
+    fn test_editar_un_publicado_exige_aprobador() {
+        assert_eq!(edit_permission(Published), Permission::CatalogPublish);
+        for status in [Draft, InReview, Archived] {
+            assert_eq!(edit_permission(status), Permission::CatalogWrite, "{}", status);
+        }
+
+        assert!(!AdminRole::CatalogEditor.allows(edit_permission(Published)));
+        assert!(AdminRole::CatalogEditor.allows(edit_permission(Draft)));
+        assert!(AdminRole::CatalogApprover.allows(edit_permission(Published)));
+        assert!(AdminRole::Owner.allows(edit_permission(Published)));
+    }
+
+    #[test]
+    fn test_ventana_de_publicacion() {
+        let now = Utc::now();
+        assert!(check_window(Some(now), Some(now + Duration::days(1))).is_empty());
+        assert!(check_window(None, Some(now)).is_empty());
+        assert!(check_window(Some(now), None).is_empty());
+        assert!(check_window(Some(now), Some(now)).contains_key("unpublish_at"));
+    }
+}
@@ -18,7 +18,9 @@ export interface Product {
   image_url: string | null;
   additional_images: string[];
   regulatory_info: Record<string, any>;
-  is_active: boolean;
+  status: 'draft' | 'in_review' | 'published' | 'archived';
+  publish_at: string | null;
+  unpublish_at: string | null;
   created_at: string;
   updated_at: string;
 }